@@ -0,0 +1,186 @@
+use crate::syntax::{unexpected, Cursor, SyntaxError, SyntaxErrorKind};
+use crate::{Arc, Graph};
+use std::collections::{HashMap, HashSet};
+
+impl Cursor<'_> {
+    fn separator(&mut self, arc_start: usize) -> Result<(), SyntaxError> {
+        match self.peek() {
+            Some((_, ',')) => {
+                self.chars.next();
+                Ok(())
+            }
+            Some((offset, ')')) => {
+                self.error(SyntaxErrorKind::MissingComma, offset)
+            }
+            Some((offset, char)) => self.error(unexpected(char, "','"), offset),
+            None => self.error(SyntaxErrorKind::UnterminatedArc, arc_start),
+        }
+    }
+
+    fn order(&mut self, arc_start: usize) -> Result<i32, SyntaxError> {
+        let start = match self.peek() {
+            Some((offset, _)) => offset,
+            None => {
+                return self.error(SyntaxErrorKind::UnterminatedArc, arc_start)
+            }
+        };
+        let mut order = String::new();
+        while let Some(&(_, char)) = self.chars.peek() {
+            if char == ')' || char == ',' || char == '(' || char.is_whitespace()
+            {
+                break;
+            }
+            order.push(char);
+            self.chars.next();
+        }
+        match order.parse::<i32>() {
+            Ok(number) if order.chars().all(|c| c.is_ascii_digit()) => {
+                Ok(number)
+            }
+            _ => self.error(SyntaxErrorKind::NonNumericOrder(order), start),
+        }
+    }
+
+    fn close(&mut self, arc_start: usize) -> Result<(), SyntaxError> {
+        match self.peek() {
+            Some((_, ')')) => {
+                self.chars.next();
+                Ok(())
+            }
+            Some((_, '(' | ',')) | None => {
+                self.error(SyntaxErrorKind::UnterminatedArc, arc_start)
+            }
+            Some((offset, char)) => self.error(unexpected(char, "')'"), offset),
+        }
+    }
+}
+
+/// Дуга без номера получает наименьший номер, ещё не занятый дугами,
+/// заходящими в ту же вершину: явные номера не пересекаются с
+/// автоматическими.
+pub(crate) fn number_arcs(
+    arcs: Vec<(String, String, Option<i32>)>,
+) -> Vec<Arc> {
+    let mut used: HashMap<&String, HashSet<i32>> = HashMap::new();
+    for (_, to, order) in &arcs {
+        if let Some(order) = order {
+            used.entry(to).or_default().insert(*order);
+        }
+    }
+    let mut next: HashMap<&String, i32> = HashMap::new();
+    let orders: Vec<i32> = arcs
+        .iter()
+        .map(|(_, to, order)| {
+            order.unwrap_or_else(|| {
+                let used = used.get(to);
+                let next = next.entry(to).or_insert(1);
+                while used.is_some_and(|used| used.contains(next)) {
+                    *next += 1;
+                }
+                *next += 1;
+                *next - 1
+            })
+        })
+        .collect();
+    arcs.into_iter()
+        .zip(orders)
+        .map(|((from, to, _), order)| Arc { from, to, order })
+        .collect()
+}
+
+impl Graph {
+    /// Дуги без номера `(a, b)` нумеруются через `number_arcs`.
+    pub fn from_arc_list(input: &str) -> Result<Graph, SyntaxError> {
+        let mut cursor = Cursor::new(input);
+        let mut vertices = HashSet::new();
+        let mut arcs = Vec::new();
+        loop {
+            let arc_start = match cursor.peek() {
+                Some((offset, '(')) => {
+                    cursor.chars.next();
+                    offset
+                }
+                Some((offset, char)) => {
+                    return cursor.error(unexpected(char, "'('"), offset)
+                }
+                None => {
+                    return cursor.error(
+                        SyntaxErrorKind::UnexpectedEnd("'('"),
+                        input.trim_end().len(),
+                    )
+                }
+            };
+            let from =
+                cursor.name(SyntaxErrorKind::UnterminatedArc, arc_start)?;
+            cursor.separator(arc_start)?;
+            let to =
+                cursor.name(SyntaxErrorKind::UnterminatedArc, arc_start)?;
+            let order = match cursor.peek() {
+                Some((_, ')')) => None,
+                _ => {
+                    cursor.separator(arc_start)?;
+                    Some(cursor.order(arc_start)?)
+                }
+            };
+            cursor.close(arc_start)?;
+            vertices.insert(from.clone());
+            vertices.insert(to.clone());
+            arcs.push((from, to, order));
+            match cursor.peek() {
+                Some((_, ',')) => {
+                    cursor.chars.next();
+                }
+                Some((offset, '(')) => {
+                    return cursor.error(SyntaxErrorKind::MissingComma, offset)
+                }
+                Some((offset, _)) => {
+                    return cursor
+                        .error(SyntaxErrorKind::TrailingGarbage, offset)
+                }
+                None => break,
+            }
+        }
+        let mut arcs = number_arcs(arcs);
+        let mut vertices: Vec<_> = vertices.into_iter().collect();
+        vertices.sort();
+        arcs.sort_by_key(|arc| arc.order);
+        Ok(Graph { vertices, arcs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Graph;
+
+    fn orders(input: &str) -> Vec<(String, i32)> {
+        let graph = Graph::from_arc_list(input).unwrap();
+        let mut orders: Vec<_> = graph
+            .arcs
+            .into_iter()
+            .map(|arc| (arc.from, arc.order))
+            .collect();
+        orders.sort();
+        orders
+    }
+
+    #[test]
+    fn implicit_orders_skip_explicit_ones() {
+        assert_eq!(
+            orders("(a, b, 2), (c, b), (d, b)"),
+            [("a".into(), 2), ("c".into(), 1), ("d".into(), 3)]
+        );
+    }
+
+    #[test]
+    fn implicit_orders_per_target() {
+        assert_eq!(
+            orders("(a, x), (b, y), (c, x), (d, y, 1)"),
+            [
+                ("a".into(), 1),
+                ("b".into(), 2),
+                ("c".into(), 2),
+                ("d".into(), 1)
+            ]
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use crate::syntax::{unexpected, Cursor, SyntaxError, SyntaxErrorKind};
+use crate::{Arc, Graph};
+use std::collections::HashMap;
+
+struct Definitions {
+    names: Vec<String>,
+    parents: HashMap<String, Vec<String>>,
+    references: Vec<(String, usize)>,
+}
+
+impl Cursor<'_> {
+    fn expect(
+        &mut self,
+        char: char,
+        expected: &'static str,
+    ) -> Result<(), SyntaxError> {
+        match self.peek() {
+            Some((_, found)) if found == char => {
+                self.chars.next();
+                Ok(())
+            }
+            Some((offset, found)) => {
+                self.error(unexpected(found, expected), offset)
+            }
+            None => self.error(
+                SyntaxErrorKind::UnexpectedEnd(expected),
+                self.input.trim_end().len(),
+            ),
+        }
+    }
+
+    fn expression_name(&mut self) -> Result<(String, bool), SyntaxError> {
+        let quoted = matches!(self.peek(), Some((_, '"')));
+        let end = self.input.trim_end().len();
+        let name =
+            self.name(SyntaxErrorKind::UnexpectedEnd("выражение"), end)?;
+        Ok((name, quoted))
+    }
+
+    fn expression(
+        &mut self,
+        scope: &mut Vec<(String, String)>,
+        definitions: &mut Definitions,
+    ) -> Result<String, SyntaxError> {
+        let start = self.peek().map_or(self.input.len(), |(offset, _)| offset);
+        let (name, quoted) = self.expression_name()?;
+
+        if name == "let" && !quoted && !matches!(self.peek(), Some((_, '('))) {
+            let (alias, _) = self.expression_name()?;
+            self.expect('=', "'='")?;
+            let value = self.expression(scope, definitions)?;
+            let keyword = self.peek().map_or(self.input.len(), |(i, _)| i);
+            match self.expression_name()? {
+                (word, false) if word == "in" => {}
+                (word, _) => {
+                    let found = word.chars().next().unwrap_or_default();
+                    return self.error(unexpected(found, "'in'"), keyword);
+                }
+            }
+            scope.push((alias, value));
+            let body = self.expression(scope, definitions);
+            scope.pop();
+            return body;
+        }
+
+        if !matches!(self.peek(), Some((_, '('))) {
+            let bound = scope.iter().rev().find(|(alias, _)| *alias == name);
+            return Ok(match bound {
+                Some((_, vertex)) => vertex.clone(),
+                None => {
+                    definitions.references.push((name.clone(), start));
+                    name
+                }
+            });
+        }
+
+        self.chars.next();
+        let mut parents = Vec::new();
+        if matches!(self.peek(), Some((_, ')'))) {
+            self.chars.next();
+        } else {
+            loop {
+                parents.push(self.expression(scope, definitions)?);
+                match self.peek() {
+                    Some((_, ',')) => {
+                        self.chars.next();
+                    }
+                    Some((_, ')')) => {
+                        self.chars.next();
+                        break;
+                    }
+                    Some((offset, found)) => {
+                        return self
+                            .error(unexpected(found, "',' или ')'"), offset)
+                    }
+                    None => {
+                        return self.error(
+                            SyntaxErrorKind::UnexpectedEnd("')'"),
+                            self.input.trim_end().len(),
+                        )
+                    }
+                }
+            }
+        }
+
+        match definitions.parents.get(&name) {
+            Some(defined) if *defined != parents => {
+                self.error(SyntaxErrorKind::ConflictingDefinition(name), start)
+            }
+            Some(_) => Ok(name),
+            None => {
+                definitions.names.push(name.clone());
+                definitions.parents.insert(name.clone(), parents);
+                Ok(name)
+            }
+        }
+    }
+}
+
+impl Graph {
+    pub fn from_call_string(input: &str) -> Result<Graph, SyntaxError> {
+        let mut cursor = Cursor::new(input);
+        let mut definitions = Definitions {
+            names: Vec::new(),
+            parents: HashMap::new(),
+            references: Vec::new(),
+        };
+        loop {
+            cursor.expression(&mut Vec::new(), &mut definitions)?;
+            if cursor.peek().is_none() {
+                break;
+            }
+        }
+        for (name, offset) in &definitions.references {
+            if !definitions.parents.contains_key(name) {
+                let kind = SyntaxErrorKind::UndefinedName(name.clone());
+                return cursor.error(kind, *offset);
+            }
+        }
+
+        let mut arcs = Vec::new();
+        for name in &definitions.names {
+            for (order, parent) in definitions.parents[name].iter().enumerate()
+            {
+                arcs.push(Arc {
+                    from: parent.clone(),
+                    to: name.clone(),
+                    order: order as i32 + 1,
+                });
+            }
+        }
+        let mut vertices = definitions.names;
+        vertices.sort();
+        arcs.sort_by_key(|arc| arc.order);
+        Ok(Graph { vertices, arcs })
+    }
+}
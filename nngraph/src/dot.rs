@@ -0,0 +1,377 @@
+use crate::arc_list::number_arcs;
+use crate::syntax::{unexpected, Cursor, SyntaxError, SyntaxErrorKind};
+use crate::Graph;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+const KEYWORDS: [&str; 6] =
+    ["node", "edge", "graph", "digraph", "subgraph", "strict"];
+
+fn is_id_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn is_keyword(id: &str, keyword: &str) -> bool {
+    id.eq_ignore_ascii_case(keyword)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn dot_id(name: &str) -> String {
+    let plain = name.chars().next().is_some_and(|c| !c.is_ascii_digit())
+        && name.chars().all(is_id_char)
+        && !KEYWORDS.iter().any(|keyword| is_keyword(name, keyword));
+    if plain {
+        name.to_string()
+    } else {
+        format!("\"{}\"", escape(name))
+    }
+}
+
+struct Id {
+    text: String,
+    offset: usize,
+    quoted: bool,
+}
+
+impl Id {
+    fn is(&self, keyword: &str) -> bool {
+        !self.quoted && is_keyword(&self.text, keyword)
+    }
+}
+
+impl Cursor<'_> {
+    fn skip_comments(&mut self) -> Result<Option<(usize, char)>, SyntaxError> {
+        while let Some((offset, char)) = self.peek() {
+            let rest = &self.input[offset..];
+            let end = if char == '#' || rest.starts_with("//") {
+                rest.find('\n').map_or(self.input.len(), |i| offset + i)
+            } else if let Some(comment) = rest.strip_prefix("/*") {
+                match comment.find("*/") {
+                    Some(i) => offset + i + 4,
+                    None => {
+                        return self.error(
+                            SyntaxErrorKind::UnterminatedComment,
+                            offset,
+                        )
+                    }
+                }
+            } else {
+                return Ok(Some((offset, char)));
+            };
+            while self.chars.next_if(|&(i, _)| i < end).is_some() {}
+        }
+        Ok(None)
+    }
+
+    fn expect_token(
+        &mut self,
+        expected: &'static str,
+    ) -> Result<(usize, char), SyntaxError> {
+        match self.skip_comments()? {
+            Some(token) => Ok(token),
+            None => self.error(
+                SyntaxErrorKind::UnexpectedEnd(expected),
+                self.input.trim_end().len(),
+            ),
+        }
+    }
+
+    fn id(&mut self, expected: &'static str) -> Result<Id, SyntaxError> {
+        let (offset, first) = self.expect_token(expected)?;
+        let mut text = String::new();
+        let quoted = first == '"' || first == '<';
+        if first == '"' {
+            self.chars.next();
+            loop {
+                match self.chars.next() {
+                    Some((_, '"')) => break,
+                    Some((_, '\\')) => match self.chars.next() {
+                        Some((_, '"')) => text.push('"'),
+                        Some((_, '\\')) => text.push('\\'),
+                        Some((_, '\n')) => {}
+                        Some((_, char)) => {
+                            text.push('\\');
+                            text.push(char);
+                        }
+                        None => {
+                            return self.error(
+                                SyntaxErrorKind::UnterminatedString,
+                                offset,
+                            )
+                        }
+                    },
+                    Some((_, char)) => text.push(char),
+                    None => {
+                        return self
+                            .error(SyntaxErrorKind::UnterminatedString, offset)
+                    }
+                }
+            }
+        } else if first == '<' {
+            self.chars.next();
+            let mut depth = 1;
+            loop {
+                match self.chars.next() {
+                    Some((_, '<')) => depth += 1,
+                    Some((_, '>')) if depth == 1 => break,
+                    Some((_, '>')) => depth -= 1,
+                    Some(_) => {}
+                    None => {
+                        return self
+                            .error(SyntaxErrorKind::UnterminatedString, offset)
+                    }
+                }
+            }
+            let end = self.chars.peek().map_or(self.input.len(), |&(i, _)| i);
+            text.push_str(&self.input[offset + 1..end - 1]);
+        } else if first == '-' || first == '.' || first.is_ascii_digit() {
+            while let Some((_, char)) = self.chars.next_if(|&(i, c)| {
+                c.is_ascii_digit() || c == '.' || (c == '-' && i == offset)
+            }) {
+                text.push(char);
+            }
+            if text.parse::<f64>().is_err() {
+                return self.error(unexpected(first, expected), offset);
+            }
+        } else if is_id_char(first) {
+            while let Some((_, char)) =
+                self.chars.next_if(|&(_, c)| is_id_char(c))
+            {
+                text.push(char);
+            }
+        } else {
+            return self.error(unexpected(first, expected), offset);
+        }
+        Ok(Id {
+            text,
+            offset,
+            quoted,
+        })
+    }
+
+    fn attributes(&mut self) -> Result<Vec<(String, Id)>, SyntaxError> {
+        let mut attributes = Vec::new();
+        while let Some((_, '[')) = self.skip_comments()? {
+            self.chars.next();
+            loop {
+                match self.expect_token("']'")? {
+                    (_, ']') => {
+                        self.chars.next();
+                        break;
+                    }
+                    (_, ',' | ';') => {
+                        self.chars.next();
+                    }
+                    _ => {
+                        let key = self.id("имя атрибута")?;
+                        let (offset, char) =
+                            self.expect_token("'=' или ']'")?;
+                        let value = if char == '=' {
+                            self.chars.next();
+                            self.id("значение атрибута")?
+                        } else {
+                            Id {
+                                text: "true".to_string(),
+                                offset,
+                                quoted: false,
+                            }
+                        };
+                        attributes.push((key.text, value));
+                    }
+                }
+            }
+        }
+        Ok(attributes)
+    }
+
+    fn edge_operator(&mut self) -> Result<bool, SyntaxError> {
+        match self.skip_comments()? {
+            Some((offset, '-')) => {
+                self.chars.next();
+                match self.chars.next() {
+                    Some((_, '>')) => Ok(true),
+                    Some((_, '-')) => self.error(
+                        SyntaxErrorKind::UnsupportedDot(
+                            "неориентированная дуга",
+                        ),
+                        offset,
+                    ),
+                    _ => self.error(unexpected('-', "'->'"), offset),
+                }
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn vertex(&mut self) -> Result<Id, SyntaxError> {
+        let id = self.id("имя вершины")?;
+        if id.is("subgraph") {
+            let kind = SyntaxErrorKind::UnsupportedDot("подграф");
+            return self.error(kind, id.offset);
+        }
+        if let Some((offset, ':')) = self.skip_comments()? {
+            let kind = SyntaxErrorKind::UnsupportedDot("порт вершины");
+            return self.error(kind, offset);
+        }
+        Ok(id)
+    }
+}
+
+struct DotGraph {
+    vertices: Vec<String>,
+    declared: HashSet<String>,
+    arcs: Vec<(String, String, Option<i32>)>,
+}
+
+impl DotGraph {
+    fn declare(&mut self, vertex: &str) {
+        if self.declared.insert(vertex.to_string()) {
+            self.vertices.push(vertex.to_string());
+        }
+    }
+
+    fn statement(&mut self, cursor: &mut Cursor) -> Result<(), SyntaxError> {
+        if let (offset, '{') = cursor.expect_token("'}'")? {
+            let kind = SyntaxErrorKind::UnsupportedDot("подграф");
+            return cursor.error(kind, offset);
+        }
+        let id = cursor.vertex()?;
+        if id.is("node") || id.is("edge") || id.is("graph") {
+            cursor.attributes()?;
+            return Ok(());
+        }
+        if let Some((_, '=')) = cursor.skip_comments()? {
+            cursor.chars.next();
+            cursor.id("значение атрибута")?;
+            return Ok(());
+        }
+
+        self.declare(&id.text);
+        let mut chain = vec![id.text];
+        while cursor.edge_operator()? {
+            let id = cursor.vertex()?;
+            self.declare(&id.text);
+            chain.push(id.text);
+        }
+        let attributes = cursor.attributes()?;
+        if chain.len() == 1 {
+            return Ok(());
+        }
+        let order = match attributes
+            .iter()
+            .rev()
+            .find(|(key, _)| key == "order")
+        {
+            Some((_, value)) => match value.text.trim().parse::<i32>() {
+                Ok(order) => Some(order),
+                Err(_) => {
+                    let kind =
+                        SyntaxErrorKind::NonNumericOrder(value.text.clone());
+                    return cursor.error(kind, value.offset);
+                }
+            },
+            None => attributes
+                .iter()
+                .rev()
+                .find(|(key, _)| key == "label")
+                .and_then(|(_, value)| value.text.trim().parse::<i32>().ok()),
+        };
+        for pair in chain.windows(2) {
+            self.arcs.push((pair[0].clone(), pair[1].clone(), order));
+        }
+        Ok(())
+    }
+}
+
+impl Graph {
+    /// Для вершин из `labels` под именем подписывается переданная строка,
+    /// дуги подписываются порядковым номером.
+    pub fn write_dot<W: Write>(
+        &self,
+        output: &mut W,
+        labels: &HashMap<String, String>,
+    ) -> io::Result<()> {
+        writeln!(output, "digraph {{")?;
+        for vertex in &self.vertices {
+            match labels.get(vertex) {
+                Some(label) => writeln!(
+                    output,
+                    "    {} [label=\"{}\\n{}\"];",
+                    dot_id(vertex),
+                    escape(vertex),
+                    escape(label)
+                )?,
+                None => writeln!(output, "    {};", dot_id(vertex))?,
+            }
+        }
+        for arc in &self.arcs {
+            writeln!(
+                output,
+                "    {} -> {} [label=\"{}\"];",
+                dot_id(&arc.from),
+                dot_id(&arc.to),
+                arc.order
+            )?;
+        }
+        writeln!(output, "}}")
+    }
+
+    /// Порядковый номер дуги берётся из атрибута `order`, иначе из `label`,
+    /// если подпись — целое число. Остальные дуги нумеруются автоматически,
+    /// как дуги без номера в списке дуг.
+    pub fn read_dot(input: &str) -> Result<Graph, SyntaxError> {
+        let mut cursor = Cursor::new(input);
+        let mut id = cursor.id("'digraph'")?;
+        if id.is("strict") {
+            id = cursor.id("'digraph'")?;
+        }
+        if id.is("graph") {
+            let kind =
+                SyntaxErrorKind::UnsupportedDot("неориентированный граф");
+            return cursor.error(kind, id.offset);
+        }
+        if !id.is("digraph") {
+            let found = id.text.chars().next().unwrap_or('"');
+            return cursor.error(unexpected(found, "'digraph'"), id.offset);
+        }
+        if !matches!(cursor.expect_token("'{'")?, (_, '{')) {
+            cursor.id("'{'")?;
+        }
+        match cursor.expect_token("'{'")? {
+            (_, '{') => {
+                cursor.chars.next();
+            }
+            (offset, char) => {
+                return cursor.error(unexpected(char, "'{'"), offset)
+            }
+        }
+
+        let mut dot = DotGraph {
+            vertices: Vec::new(),
+            declared: HashSet::new(),
+            arcs: Vec::new(),
+        };
+        loop {
+            match cursor.expect_token("'}'")? {
+                (_, '}') => {
+                    cursor.chars.next();
+                    break;
+                }
+                (_, ';') => {
+                    cursor.chars.next();
+                }
+                _ => dot.statement(&mut cursor)?,
+            }
+        }
+        if let Some((offset, _)) = cursor.skip_comments()? {
+            return cursor.error(SyntaxErrorKind::TrailingGraph, offset);
+        }
+        Ok(Graph {
+            vertices: dot.vertices,
+            arcs: number_arcs(dot.arcs),
+        })
+    }
+}
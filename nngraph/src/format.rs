@@ -0,0 +1,128 @@
+use crate::{quote_name, Graph, ReadError, SyntaxError, XmlOptions};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use xml::writer::EmitterConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Xml,
+    Json,
+    GraphMl,
+    Dot,
+}
+
+impl GraphFormat {
+    pub fn from_path(path: &str) -> Option<GraphFormat> {
+        let extension = Path::new(path).extension()?.to_str()?;
+        match extension.to_lowercase().as_str() {
+            "xml" => Some(GraphFormat::Xml),
+            "json" => Some(GraphFormat::Json),
+            "graphml" => Some(GraphFormat::GraphMl),
+            "dot" | "gv" => Some(GraphFormat::Dot),
+            _ => None,
+        }
+    }
+
+    pub fn sniff(input: &str) -> GraphFormat {
+        let input = input.trim_start_matches('\u{feff}').trim_start();
+        if input.starts_with('{') {
+            GraphFormat::Json
+        } else if input.starts_with('<') && input.contains("<graphml") {
+            GraphFormat::GraphMl
+        } else if input.starts_with('<') {
+            GraphFormat::Xml
+        } else {
+            GraphFormat::Dot
+        }
+    }
+
+    /// Формат определяется по расширению файла, а если оно незнакомо, то
+    /// по содержимому.
+    pub fn detect(path: &str, input: &str) -> GraphFormat {
+        GraphFormat::from_path(path)
+            .unwrap_or_else(|| GraphFormat::sniff(input))
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Xml(ReadError),
+    Json(serde_json::Error),
+    Dot(SyntaxError),
+    DuplicateVertex(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(err) => write!(f, "{err}"),
+            LoadError::Xml(err) => write!(f, "{err}"),
+            LoadError::Json(err) => write!(f, "ошибка в JSON: {err}"),
+            LoadError::Dot(err) => write!(f, "{err}"),
+            LoadError::DuplicateVertex(vertex) => {
+                write!(f, "вершина {} объявлена повторно", quote_name(vertex))
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl Graph {
+    pub fn read_json(input: &str) -> Result<Graph, serde_json::Error> {
+        serde_json::from_str(input)
+    }
+
+    /// Читает граф в любом формате. Повторно объявленные вершины
+    /// проверяются одинаково для всех форматов.
+    pub fn read(input: &str, format: GraphFormat) -> Result<Graph, LoadError> {
+        let g = match format {
+            GraphFormat::Xml => {
+                Graph::read_xml(input.as_bytes()).map_err(LoadError::Xml)
+            }
+            GraphFormat::Json => {
+                Graph::read_json(input).map_err(LoadError::Json)
+            }
+            GraphFormat::GraphMl => {
+                Graph::read_graphml(input.as_bytes()).map_err(LoadError::Xml)
+            }
+            GraphFormat::Dot => Graph::read_dot(input).map_err(LoadError::Dot),
+        }?;
+        if let Some(vertex) = g.duplicate_vertex() {
+            return Err(LoadError::DuplicateVertex(vertex.clone()));
+        }
+        Ok(g)
+    }
+
+    /// Читает граф из файла, формат определяется через
+    /// `GraphFormat::detect`.
+    pub fn load(path: &str) -> Result<Graph, LoadError> {
+        let input = fs::read_to_string(path).map_err(LoadError::Io)?;
+        Graph::read(&input, GraphFormat::detect(path, &input))
+    }
+
+    pub fn write<W: Write>(
+        &self,
+        mut output: W,
+        format: GraphFormat,
+    ) -> io::Result<()> {
+        match format {
+            GraphFormat::Xml => self.write_xml(output, &XmlOptions::default()),
+            GraphFormat::Json => {
+                serde_json::to_writer_pretty(&mut output, self)?;
+                writeln!(output)
+            }
+            GraphFormat::GraphMl => {
+                let mut writer = EmitterConfig::new()
+                    .perform_indent(true)
+                    .create_writer(output);
+                self.write_graphml(&mut writer).map_err(io::Error::other)
+            }
+            GraphFormat::Dot => self.write_dot(&mut output, &HashMap::new()),
+        }
+    }
+}
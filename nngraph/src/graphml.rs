@@ -0,0 +1,266 @@
+use crate::arc_list::number_arcs;
+use crate::xml::{ReadError, ReadErrorKind};
+use crate::Graph;
+use std::collections::HashSet;
+use std::io::{BufReader, Read, Write};
+use xml::attribute::OwnedAttribute;
+use xml::common::{Position, TextPosition};
+use xml::reader::{EventReader, XmlEvent as ReaderEvent};
+use xml::writer::{EventWriter, Result as WriteResult, XmlEvent};
+
+const GRAPHML_NS: &str = "http://graphml.graphdrawing.org/xmlns";
+
+fn attribute(
+    attributes: &[OwnedAttribute],
+    element: &'static str,
+    attribute: &'static str,
+    position: TextPosition,
+) -> Result<String, ReadError> {
+    match attributes.iter().find(|a| a.name.local_name == attribute) {
+        Some(a) => Ok(a.value.clone()),
+        None => Err(ReadError::new(
+            ReadErrorKind::MissingAttribute { element, attribute },
+            position,
+        )),
+    }
+}
+
+struct PartialEdge {
+    from: String,
+    to: String,
+    order: Option<i32>,
+}
+
+impl Graph {
+    pub fn write_graphml<T>(
+        &self,
+        writer: &mut EventWriter<T>,
+    ) -> WriteResult<()>
+    where
+        T: Write,
+    {
+        writer
+            .write(XmlEvent::start_element("graphml").default_ns(GRAPHML_NS))?;
+        writer.write(
+            XmlEvent::start_element("key")
+                .attr("id", "order")
+                .attr("for", "edge")
+                .attr("attr.name", "order")
+                .attr("attr.type", "int"),
+        )?;
+        writer.write(XmlEvent::end_element())?;
+        writer.write(
+            XmlEvent::start_element("graph")
+                .attr("id", "G")
+                .attr("edgedefault", "directed"),
+        )?;
+        for vertex in &self.vertices {
+            writer.write(XmlEvent::start_element("node").attr("id", vertex))?;
+            writer.write(XmlEvent::end_element())?;
+        }
+        for arc in &self.arcs {
+            writer.write(
+                XmlEvent::start_element("edge")
+                    .attr("source", &arc.from)
+                    .attr("target", &arc.to),
+            )?;
+            writer
+                .write(XmlEvent::start_element("data").attr("key", "order"))?;
+            writer.write(XmlEvent::characters(&arc.order.to_string()))?;
+            writer.write(XmlEvent::end_element())?;
+            writer.write(XmlEvent::end_element())?;
+        }
+        writer.write(XmlEvent::end_element())?;
+        writer.write(XmlEvent::end_element())?;
+        Ok(())
+    }
+
+    /// Порядковый номер дуги берётся из `<data>` с ключом, объявленным с
+    /// `attr.name="order"`; дуги без него нумеруются автоматически, как
+    /// дуги без номера в списке дуг. Остальные элементы и данные
+    /// пропускаются.
+    pub fn read_graphml<R: Read>(input: R) -> Result<Graph, ReadError> {
+        let mut parser = EventReader::new(BufReader::new(input));
+        let mut graph = Graph::default();
+        let mut order_keys = HashSet::new();
+        let mut edges = Vec::new();
+        let mut found = false;
+        let mut skipped = 0;
+        let mut edge: Option<PartialEdge> = None;
+        let mut order: Option<TextPosition> = None;
+        let mut text = String::new();
+
+        loop {
+            let event = parser.next();
+            let position = parser.position();
+            match event {
+                Ok(ReaderEvent::StartElement {
+                    name, attributes, ..
+                }) => {
+                    if skipped > 0 {
+                        skipped += 1;
+                        continue;
+                    }
+                    match name.local_name.as_str() {
+                        "graphml" if !found => {}
+                        "key" => {
+                            let target = attributes
+                                .iter()
+                                .find(|a| a.name.local_name == "for")
+                                .map_or("all", |a| a.value.as_str());
+                            let is_order = attributes.iter().any(|a| {
+                                a.name.local_name == "attr.name"
+                                    && a.value == "order"
+                            });
+                            if is_order && (target == "edge" || target == "all")
+                            {
+                                order_keys.insert(attribute(
+                                    &attributes,
+                                    "key",
+                                    "id",
+                                    position,
+                                )?);
+                            }
+                            skipped = 1;
+                        }
+                        "graph" if !found => found = true,
+                        "graph" => {
+                            return Err(ReadError::new(
+                                ReadErrorKind::UnexpectedElement(
+                                    name.local_name,
+                                ),
+                                position,
+                            ))
+                        }
+                        "node" if found && edge.is_none() => {
+                            graph.vertices.push(attribute(
+                                &attributes,
+                                "node",
+                                "id",
+                                position,
+                            )?);
+                            skipped = 1;
+                        }
+                        "edge" if found && edge.is_none() => {
+                            edge = Some(PartialEdge {
+                                from: attribute(
+                                    &attributes,
+                                    "edge",
+                                    "source",
+                                    position,
+                                )?,
+                                to: attribute(
+                                    &attributes,
+                                    "edge",
+                                    "target",
+                                    position,
+                                )?,
+                                order: None,
+                            });
+                        }
+                        "data"
+                            if edge.is_some()
+                                && attributes.iter().any(|a| {
+                                    a.name.local_name == "key"
+                                        && order_keys.contains(&a.value)
+                                }) =>
+                        {
+                            order = Some(position);
+                            text.clear();
+                        }
+                        _ => skipped = 1,
+                    }
+                }
+                Ok(ReaderEvent::EndElement { name }) => {
+                    if skipped > 0 {
+                        skipped -= 1;
+                        continue;
+                    }
+                    match name.local_name.as_str() {
+                        "data" => {
+                            let position = order.take().unwrap_or(position);
+                            let number = match text.trim().parse::<i32>() {
+                                Ok(number) => number,
+                                Err(_) => {
+                                    return Err(ReadError::new(
+                                        ReadErrorKind::InvalidOrder(
+                                            std::mem::take(&mut text),
+                                        ),
+                                        position,
+                                    ))
+                                }
+                            };
+                            if let Some(edge) = &mut edge {
+                                edge.order = Some(number);
+                            }
+                        }
+                        "edge" => {
+                            if let Some(edge) = edge.take() {
+                                edges.push((edge.from, edge.to, edge.order));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(ReaderEvent::Characters(chars))
+                | Ok(ReaderEvent::CData(chars))
+                | Ok(ReaderEvent::Whitespace(chars)) => {
+                    if order.is_some() {
+                        text.push_str(&chars);
+                    }
+                }
+                Ok(ReaderEvent::EndDocument) => break,
+                Ok(_) => {}
+                Err(err) => {
+                    return Err(ReadError::new(
+                        ReadErrorKind::Syntax(err.msg().to_string()),
+                        err.position(),
+                    ))
+                }
+            }
+        }
+
+        if !found {
+            return Err(ReadError::new(
+                ReadErrorKind::MissingGraph,
+                parser.position(),
+            ));
+        }
+        graph.arcs = number_arcs(edges);
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Graph;
+
+    const HEADER: &str = r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+<key id="n" for="edge" attr.name="order" attr.type="int"/>
+<graph id="G" edgedefault="directed">
+<node id="a"/><node id="b"/><node id="z"/>"#;
+
+    #[test]
+    fn edges_without_order_use_free_numbers() {
+        let input = format!(
+            r#"{HEADER}
+<edge source="b" target="a"><data key="n">2</data></edge>
+<edge source="z" target="a"/>
+</graph></graphml>"#
+        );
+        let graph = Graph::read_graphml(input.as_bytes()).unwrap();
+        let nodes = graph.nodes().unwrap();
+        assert_eq!(nodes["a"].parents, ["z", "b"]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let graph = Graph::from_arc_list("(b, a, 2), (z, a, 1)").unwrap();
+        let mut output = Vec::new();
+        graph
+            .write(&mut output, crate::GraphFormat::GraphMl)
+            .unwrap();
+        let read = Graph::read_graphml(output.as_slice()).unwrap();
+        assert_eq!(read.nodes().unwrap()["a"].parents, ["z", "b"]);
+    }
+}
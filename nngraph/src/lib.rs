@@ -0,0 +1,100 @@
+//! Ориентированный граф с упорядоченными дугами, общий для всех заданий.
+
+mod arc_list;
+mod call;
+mod dot;
+mod format;
+mod graphml;
+mod natural;
+mod syntax;
+mod traverse;
+mod validate;
+mod xml;
+
+pub use format::{GraphFormat, LoadError};
+pub use natural::natural_cmp;
+pub use syntax::{is_name_char, quote_name, SyntaxError, SyntaxErrorKind};
+pub use traverse::{find_root, find_roots, topological_order, Cycle};
+pub use validate::{
+    Issue, Problem, Severity, Strictness, UndeclaredVertex, UndeclaredVertices,
+};
+pub use xml::{
+    Encoding, ReadError, ReadErrorKind, XmlOptions, GRAPH_NAMESPACE,
+    GRAPH_VERSION,
+};
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Arc {
+    pub from: String,
+    pub to: String,
+    pub order: i32,
+}
+
+impl fmt::Display for Arc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "({}, {}, {})",
+            quote_name(&self.from),
+            quote_name(&self.to),
+            self.order
+        )
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Graph {
+    pub vertices: Vec<String>,
+    pub arcs: Vec<Arc>,
+}
+
+#[derive(Debug)]
+pub struct Node {
+    pub name: String,
+    pub children: Vec<String>,
+    pub parents: Vec<String>,
+}
+
+impl Node {
+    pub fn new(name: String) -> Node {
+        Node {
+            name,
+            children: Vec::new(),
+            parents: Vec::new(),
+        }
+    }
+}
+
+impl Graph {
+    /// Родители каждой вершины упорядочены по `order` заходящих в неё дуг,
+    /// дуги с одинаковым номером — по имени начала, так что результат не
+    /// зависит от порядка дуг в файле.
+    /// Дуги, ссылающиеся на необъявленные вершины, дают ошибку.
+    pub fn nodes(&self) -> Result<HashMap<String, Node>, UndeclaredVertices> {
+        let undeclared = self.undeclared_vertices();
+        if !undeclared.is_empty() {
+            return Err(UndeclaredVertices(undeclared));
+        }
+        let mut nodes: HashMap<String, Node> = HashMap::new();
+        for vert in &self.vertices {
+            nodes.insert(vert.clone(), Node::new(vert.clone()));
+        }
+        let mut arcs: Vec<&Arc> = self.arcs.iter().collect();
+        arcs.sort_by_key(|arc| (arc.order, &arc.from, &arc.to));
+        for arc in arcs {
+            if let Some(from) = nodes.get_mut(&arc.from) {
+                from.children.push(arc.to.clone());
+            }
+            if let Some(to) = nodes.get_mut(&arc.to) {
+                to.parents.push(arc.from.clone());
+            }
+        }
+        Ok(nodes)
+    }
+}
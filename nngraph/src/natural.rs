@@ -0,0 +1,86 @@
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::str::Chars;
+
+fn digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+        digits.push(c);
+    }
+    digits
+}
+
+/// Сравнивает строки так, что последовательности цифр сравниваются как
+/// числа: `x2` меньше `x10`. При равных значениях меньше число с меньшим
+/// количеством ведущих нулей.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+    loop {
+        let ordering = match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x = digits(&mut a);
+                let y = digits(&mut b);
+                let (x_value, y_value) =
+                    (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                x_value
+                    .len()
+                    .cmp(&y_value.len())
+                    .then_with(|| x_value.cmp(y_value))
+                    .then_with(|| x.len().cmp(&y.len()))
+            }
+            (Some(x), Some(y)) => {
+                a.next();
+                b.next();
+                x.cmp(&y)
+            }
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_compare_by_value() {
+        assert_eq!(natural_cmp("x2", "x10"), Ordering::Less);
+        assert_eq!(natural_cmp("x10", "x9"), Ordering::Greater);
+        assert_eq!(natural_cmp("a1b2", "a1b10"), Ordering::Less);
+        assert_eq!(natural_cmp("v12", "v12"), Ordering::Equal);
+    }
+
+    #[test]
+    fn leading_zeros_break_ties() {
+        assert_eq!(natural_cmp("x01", "x1"), Ordering::Greater);
+        assert_eq!(natural_cmp("x001", "x2"), Ordering::Less);
+        assert_eq!(natural_cmp("x0", "x00"), Ordering::Less);
+    }
+
+    #[test]
+    fn prefixes_and_letters() {
+        assert_eq!(natural_cmp("x", "x1"), Ordering::Less);
+        assert_eq!(natural_cmp("b", "a10"), Ordering::Greater);
+        assert_eq!(natural_cmp("1", "a"), Ordering::Less);
+        assert_eq!(natural_cmp("", ""), Ordering::Equal);
+    }
+
+    #[test]
+    fn long_numbers() {
+        let big = "n123456789012345678901234567890";
+        assert_eq!(natural_cmp(big, "n99"), Ordering::Greater);
+    }
+
+    #[test]
+    fn sorts_vertex_names() {
+        let mut names = vec!["v10", "v2", "a", "v1", "v02"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, ["a", "v1", "v2", "v02", "v10"]);
+    }
+}
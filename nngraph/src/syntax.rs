@@ -0,0 +1,351 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+pub fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.'
+}
+
+/// Имена `let` и `in` совпадают с ключевыми словами строки вызова и
+/// поэтому тоже записываются в кавычках.
+pub fn quote_name(name: &str) -> Cow<'_, str> {
+    let keyword = name == "let" || name == "in";
+    if !keyword && !name.is_empty() && name.chars().all(is_name_char) {
+        return Cow::Borrowed(name);
+    }
+    let mut quoted = String::from('"');
+    for c in name.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    Cow::Owned(quoted)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SyntaxErrorKind {
+    UnexpectedChar { found: char, expected: &'static str },
+    MissingComma,
+    NonNumericOrder(String),
+    UnterminatedArc,
+    TrailingGarbage,
+    UnexpectedEnd(&'static str),
+    UnterminatedString,
+    InvalidEscape(char),
+    EmptyName,
+    ConflictingDefinition(String),
+    UndefinedName(String),
+    UnterminatedComment,
+    TrailingGraph,
+    UnsupportedDot(&'static str),
+}
+
+impl fmt::Display for SyntaxErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SyntaxErrorKind::UnexpectedChar { found, expected } => {
+                write!(f, "неожиданный символ '{found}', ожидалось {expected}")
+            }
+            SyntaxErrorKind::MissingComma => write!(f, "пропущена запятая"),
+            SyntaxErrorKind::NonNumericOrder(order) => {
+                write!(
+                    f,
+                    "порядковый номер дуги '{order}' не является целым числом"
+                )
+            }
+            SyntaxErrorKind::UnterminatedArc => {
+                write!(f, "дуга не закрыта скобкой ')'")
+            }
+            SyntaxErrorKind::TrailingGarbage => {
+                write!(f, "лишние символы после дуги")
+            }
+            SyntaxErrorKind::UnexpectedEnd(expected) => {
+                write!(f, "неожиданный конец ввода, ожидалось {expected}")
+            }
+            SyntaxErrorKind::UnterminatedString => {
+                write!(f, "не закрыта кавычка в имени вершины")
+            }
+            SyntaxErrorKind::InvalidEscape(char) => {
+                write!(f, "неизвестная escape-последовательность '\\{char}'")
+            }
+            SyntaxErrorKind::EmptyName => write!(f, "пустое имя вершины"),
+            SyntaxErrorKind::ConflictingDefinition(name) => {
+                let name = quote_name(name);
+                write!(
+                    f,
+                    "вершина {name} уже встречалась с другими аргументами"
+                )
+            }
+            SyntaxErrorKind::UndefinedName(name) => {
+                let name = quote_name(name);
+                write!(f, "вершина {name} нигде не определена")
+            }
+            SyntaxErrorKind::UnterminatedComment => {
+                write!(f, "не закрыт комментарий '/*'")
+            }
+            SyntaxErrorKind::TrailingGraph => {
+                write!(f, "лишние символы после описания графа")
+            }
+            SyntaxErrorKind::UnsupportedDot(what) => {
+                write!(f, "{what} в формате DOT не поддерживается")
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SyntaxError {
+    pub kind: SyntaxErrorKind,
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+    pub snippet: String,
+}
+
+impl SyntaxError {
+    pub(crate) fn new(
+        kind: SyntaxErrorKind,
+        input: &str,
+        offset: usize,
+    ) -> SyntaxError {
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |i| offset + i);
+        SyntaxError {
+            kind,
+            offset,
+            line: input[..offset].matches('\n').count() + 1,
+            column: input[line_start..offset].chars().count() + 1,
+            snippet: input[line_start..line_end]
+                .trim_end_matches('\r')
+                .to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "строка {}, столбец {} (байт {}): {}",
+            self.line, self.column, self.offset, self.kind
+        )?;
+        writeln!(f, "{}", self.snippet)?;
+        write!(f, "{}^", " ".repeat(self.column - 1))
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+pub(crate) fn unexpected(
+    found: char,
+    expected: &'static str,
+) -> SyntaxErrorKind {
+    SyntaxErrorKind::UnexpectedChar { found, expected }
+}
+
+pub(crate) struct Cursor<'a> {
+    pub(crate) input: &'a str,
+    pub(crate) chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Cursor<'a> {
+    pub(crate) fn new(input: &'a str) -> Cursor<'a> {
+        Cursor {
+            input,
+            chars: input.char_indices().peekable(),
+        }
+    }
+
+    pub(crate) fn error<T>(
+        &self,
+        kind: SyntaxErrorKind,
+        offset: usize,
+    ) -> Result<T, SyntaxError> {
+        Err(SyntaxError::new(kind, self.input, offset))
+    }
+
+    pub(crate) fn peek(&mut self) -> Option<(usize, char)> {
+        while let Some((_, char)) = self.chars.peek() {
+            if !char.is_whitespace() {
+                break;
+            }
+            self.chars.next();
+        }
+        self.chars.peek().copied()
+    }
+
+    pub(crate) fn name(
+        &mut self,
+        end: SyntaxErrorKind,
+        end_offset: usize,
+    ) -> Result<String, SyntaxError> {
+        match self.peek() {
+            Some((offset, '"')) => {
+                self.chars.next();
+                self.quoted_name(offset)
+            }
+            Some((_, char)) if is_name_char(char) => {
+                let mut name = String::new();
+                while let Some(&(_, char)) = self.chars.peek() {
+                    if !is_name_char(char) {
+                        break;
+                    }
+                    name.push(char);
+                    self.chars.next();
+                }
+                Ok(name)
+            }
+            Some((offset, char)) => {
+                self.error(unexpected(char, "имя вершины"), offset)
+            }
+            None => self.error(end, end_offset),
+        }
+    }
+
+    fn quoted_name(&mut self, start: usize) -> Result<String, SyntaxError> {
+        let mut name = String::new();
+        loop {
+            match self.chars.next() {
+                Some((_, '"')) => break,
+                Some((offset, '\\')) => match self.chars.next() {
+                    Some((_, '"')) => name.push('"'),
+                    Some((_, '\\')) => name.push('\\'),
+                    Some((_, 'n')) => name.push('\n'),
+                    Some((_, 't')) => name.push('\t'),
+                    Some((_, char)) => {
+                        return self.error(
+                            SyntaxErrorKind::InvalidEscape(char),
+                            offset,
+                        )
+                    }
+                    None => {
+                        return self
+                            .error(SyntaxErrorKind::UnterminatedString, start)
+                    }
+                },
+                Some((_, char)) => name.push(char),
+                None => {
+                    return self
+                        .error(SyntaxErrorKind::UnterminatedString, start)
+                }
+            }
+        }
+        if name.trim().is_empty() {
+            return self.error(SyntaxErrorKind::EmptyName, start);
+        }
+        Ok(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{quote_name, Arc, Graph, SyntaxError, SyntaxErrorKind};
+
+    fn error(input: &str) -> SyntaxError {
+        Graph::from_arc_list(input).unwrap_err()
+    }
+
+    #[test]
+    fn position_on_second_line() {
+        let err = error("(a, b, 1),\n(c d, 2)");
+        assert_eq!(
+            err.kind,
+            SyntaxErrorKind::UnexpectedChar {
+                found: 'd',
+                expected: "','",
+            }
+        );
+        assert_eq!((err.line, err.column, err.offset), (2, 4, 14));
+        assert_eq!(err.snippet, "(c d, 2)");
+    }
+
+    #[test]
+    fn column_counts_characters() {
+        let err = error("(я, б, x)");
+        assert_eq!(err.kind, SyntaxErrorKind::NonNumericOrder("x".into()));
+        assert_eq!((err.line, err.column), (1, 8));
+        assert_eq!(err.offset, "(я, б, ".len());
+    }
+
+    #[test]
+    fn caret_under_column() {
+        let err = error("(a, b, 1),\r\n(a b, 2)");
+        assert_eq!(err.snippet, "(a b, 2)");
+        assert_eq!(
+            err.to_string(),
+            "строка 2, столбец 4 (байт 15): неожиданный символ 'b', \
+             ожидалось ','\n(a b, 2)\n   ^"
+        );
+    }
+
+    #[test]
+    fn kinds() {
+        assert_eq!(error("(a, b) (c, d)").kind, SyntaxErrorKind::MissingComma);
+        assert_eq!(
+            error("(a b)").kind,
+            SyntaxErrorKind::UnexpectedChar {
+                found: 'b',
+                expected: "','",
+            }
+        );
+        assert_eq!(error("(a, b, 1").kind, SyntaxErrorKind::UnterminatedArc);
+        assert_eq!(error("(a, b, 1) x").kind, SyntaxErrorKind::TrailingGarbage);
+        assert_eq!(error("").kind, SyntaxErrorKind::UnexpectedEnd("'('"));
+        assert_eq!(
+            error("(a, b, -1)").kind,
+            SyntaxErrorKind::NonNumericOrder("-1".into())
+        );
+    }
+
+    #[test]
+    fn quoted_names() {
+        let graph = Graph::from_arc_list(r#"("a b", "c\"d\\", 1)"#).unwrap();
+        assert_eq!(graph.arcs[0].from, "a b");
+        assert_eq!(graph.arcs[0].to, "c\"d\\");
+        let graph = Graph::from_arc_list(r#"("x\ny\tz", "1", 1)"#).unwrap();
+        assert_eq!(graph.arcs[0].from, "x\ny\tz");
+        assert_eq!(graph.arcs[0].to, "1");
+    }
+
+    #[test]
+    fn quote_name_escapes() {
+        assert_eq!(quote_name("a_1.b"), "a_1.b");
+        assert_eq!(quote_name("a b"), "\"a b\"");
+        assert_eq!(quote_name("c\"d\\"), r#""c\"d\\""#);
+        assert_eq!(quote_name("x\ny\tz"), r#""x\ny\tz""#);
+        assert_eq!(quote_name(""), "\"\"");
+    }
+
+    #[test]
+    fn quoted_round_trip() {
+        for name in ["a b", "c\"d\\", "x\ny", "(,)"] {
+            let arc = Arc {
+                from: name.into(),
+                to: "b".into(),
+                order: 1,
+            };
+            let graph = Graph::from_arc_list(&arc.to_string()).unwrap();
+            assert_eq!(graph.arcs[0].from, name);
+        }
+    }
+
+    #[test]
+    fn quoted_name_errors() {
+        assert_eq!(
+            error(r#"("a, b, 1)"#).kind,
+            SyntaxErrorKind::UnterminatedString
+        );
+        let err = error(r#"("a\q", b, 1)"#);
+        assert_eq!(err.kind, SyntaxErrorKind::InvalidEscape('q'));
+        assert_eq!(err.column, 4);
+        assert_eq!(error(r#"(" ", b, 1)"#).kind, SyntaxErrorKind::EmptyName);
+    }
+}
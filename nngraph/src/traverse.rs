@@ -0,0 +1,307 @@
+use crate::{quote_name, Graph, Node};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+pub fn find_roots(nodes: &HashMap<String, Node>) -> Vec<String> {
+    let mut roots: Vec<String> = nodes
+        .iter()
+        .filter(|(_, node)| node.children.is_empty())
+        .map(|(name, _)| name.clone())
+        .collect();
+    roots.sort();
+    roots
+}
+
+pub fn find_root(nodes: &HashMap<String, Node>) -> Option<String> {
+    let mut roots = find_roots(nodes);
+    if roots.len() != 1 {
+        return None;
+    }
+    roots.pop()
+}
+
+/// Вершины, от которых зависит `root`, в таком порядке, что родители
+/// каждой вершины стоят раньше неё. Сама `root` оказывается последней.
+/// Обход не рекурсивный, глубина графа не ограничена размером стека.
+pub fn topological_order(
+    root: &str,
+    nodes: &HashMap<String, Node>,
+) -> Vec<String> {
+    let mut order = Vec::new();
+    let mut visited = HashSet::new();
+    let mut stack = vec![(root, false)];
+    while let Some((name, expanded)) = stack.pop() {
+        if expanded {
+            order.push(name.to_string());
+            continue;
+        }
+        if !visited.insert(name) {
+            continue;
+        }
+        stack.push((name, true));
+        for parent in nodes[name].parents.iter().rev() {
+            if !visited.contains(parent.as_str()) {
+                stack.push((parent, false));
+            }
+        }
+    }
+    order
+}
+
+#[derive(Debug)]
+pub struct Cycle(pub Vec<String>);
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for vertex in &self.0 {
+            write!(f, "{} -> ", quote_name(vertex))?;
+        }
+        write!(f, "{}", quote_name(&self.0[0]))
+    }
+}
+
+impl Graph {
+    fn successors(&self) -> Vec<Vec<usize>> {
+        let index: HashMap<&String, usize> = self
+            .vertices
+            .iter()
+            .enumerate()
+            .map(|(i, vertex)| (vertex, i))
+            .collect();
+        let mut successors = vec![Vec::new(); self.vertices.len()];
+        for arc in &self.arcs {
+            if let (Some(&from), Some(&to)) =
+                (index.get(&arc.from), index.get(&arc.to))
+            {
+                successors[from].push(to);
+            }
+        }
+        successors
+    }
+
+    pub fn cycles(&self) -> Vec<Cycle> {
+        let successors = self.successors();
+        strongly_connected(&successors)
+            .into_iter()
+            .filter_map(|component| cycle_in(&component, &successors))
+            .map(|cycle| {
+                Cycle(cycle.iter().map(|&i| self.vertices[i].clone()).collect())
+            })
+            .collect()
+    }
+}
+
+fn strongly_connected(successors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let n = successors.len();
+    let mut index = vec![usize::MAX; n];
+    let mut lowlink = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut components = Vec::new();
+
+    for start in 0..n {
+        if index[start] != usize::MAX {
+            continue;
+        }
+        let mut calls = vec![(start, 0)];
+        index[start] = next_index;
+        lowlink[start] = next_index;
+        next_index += 1;
+        stack.push(start);
+        on_stack[start] = true;
+
+        while let Some((v, next)) = calls.last_mut() {
+            let v = *v;
+            if let Some(&w) = successors[v].get(*next) {
+                *next += 1;
+                if index[w] == usize::MAX {
+                    index[w] = next_index;
+                    lowlink[w] = next_index;
+                    next_index += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    calls.push((w, 0));
+                } else if on_stack[w] {
+                    lowlink[v] = lowlink[v].min(index[w]);
+                }
+                continue;
+            }
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[v]);
+            }
+            if lowlink[v] == index[v] {
+                let mut component = Vec::new();
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    components.sort_by_key(|component| component.iter().min().copied());
+    components
+}
+
+fn cycle_in(
+    component: &[usize],
+    successors: &[Vec<usize>],
+) -> Option<Vec<usize>> {
+    let start = *component.iter().min()?;
+    if component.len() == 1 && !successors[start].contains(&start) {
+        return None;
+    }
+
+    let members: HashSet<&usize> = component.iter().collect();
+    let mut previous = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(v) = queue.pop_front() {
+        for &w in &successors[v] {
+            if w == start {
+                let mut cycle = vec![v];
+                while let Some(&u) = previous.get(cycle.last()?) {
+                    cycle.push(u);
+                }
+                cycle.reverse();
+                return Some(cycle);
+            }
+            if members.contains(&w) && !previous.contains_key(&w) {
+                previous.insert(w, v);
+                queue.push_back(w);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{topological_order, Arc, Graph};
+
+    fn graph(vertices: &[&str], arcs: &[(&str, &str)]) -> Graph {
+        Graph {
+            vertices: vertices
+                .iter()
+                .map(|vertex| vertex.to_string())
+                .collect(),
+            arcs: arcs
+                .iter()
+                .map(|&(from, to)| Arc {
+                    from: from.into(),
+                    to: to.into(),
+                    order: 1,
+                })
+                .collect(),
+        }
+    }
+
+    fn cycles(graph: &Graph) -> Vec<String> {
+        graph
+            .cycles()
+            .iter()
+            .map(|cycle| cycle.to_string())
+            .collect()
+    }
+
+    /// Цепочка `v0 -> v1 -> … -> v{length - 1}`, с `closed` — замкнутая.
+    fn chain(length: usize, closed: bool) -> Graph {
+        let mut graph = Graph::default();
+        for i in 0..length {
+            graph.vertices.push(format!("v{i}"));
+        }
+        for i in 1..length {
+            graph.arcs.push(Arc {
+                from: format!("v{}", i - 1),
+                to: format!("v{i}"),
+                order: 1,
+            });
+        }
+        if closed {
+            graph.arcs.push(Arc {
+                from: format!("v{}", length - 1),
+                to: "v0".into(),
+                order: 1,
+            });
+        }
+        graph
+    }
+
+    #[test]
+    fn acyclic() {
+        let graph =
+            graph(&["a", "b", "c"], &[("b", "a"), ("c", "a"), ("c", "b")]);
+        assert!(graph.cycles().is_empty());
+    }
+
+    #[test]
+    fn self_loop() {
+        let graph = graph(&["a", "b"], &[("a", "a"), ("b", "a")]);
+        assert_eq!(cycles(&graph), ["a -> a"]);
+    }
+
+    #[test]
+    fn several_components() {
+        let graph = graph(
+            &["a", "b", "c", "d", "e", "f"],
+            &[
+                ("a", "b"),
+                ("b", "a"),
+                ("c", "d"),
+                ("d", "e"),
+                ("e", "c"),
+                ("e", "f"),
+                ("f", "f"),
+            ],
+        );
+        assert_eq!(
+            cycles(&graph),
+            ["a -> b -> a", "c -> d -> e -> c", "f -> f"]
+        );
+    }
+
+    #[test]
+    fn shortest_cycle_through_smallest_vertex() {
+        let graph = graph(
+            &["a", "b", "c", "d"],
+            &[("a", "b"), ("b", "c"), ("c", "d"), ("d", "a"), ("b", "a")],
+        );
+        assert_eq!(cycles(&graph), ["a -> b -> a"]);
+    }
+
+    #[test]
+    fn deep_chains() {
+        assert!(chain(200_000, false).cycles().is_empty());
+        let cycles = chain(200_000, true).cycles();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].0.len(), 200_000);
+        assert_eq!(cycles[0].0[0], "v0");
+    }
+
+    #[test]
+    fn parents_before_children() {
+        let graph = graph(
+            &["a", "b", "c", "d"],
+            &[("b", "a"), ("c", "a"), ("d", "b"), ("d", "c")],
+        );
+        let nodes = graph.nodes().unwrap();
+        assert_eq!(topological_order("a", &nodes), ["d", "b", "c", "a"]);
+        assert_eq!(topological_order("c", &nodes), ["d", "c"]);
+    }
+
+    #[test]
+    fn deep_topological_order() {
+        let nodes = chain(200_000, false).nodes().unwrap();
+        let order = topological_order("v199999", &nodes);
+        assert_eq!(order.len(), 200_000);
+        assert_eq!(
+            (order[0].as_str(), order[199_999].as_str()),
+            ("v0", "v199999")
+        );
+    }
+}
@@ -0,0 +1,376 @@
+use crate::{quote_name, Arc, Graph};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Strictness {
+    Lenient,
+    Normal,
+    Strict,
+}
+
+impl FromStr for Strictness {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lenient" => Ok(Strictness::Lenient),
+            "normal" => Ok(Strictness::Normal),
+            "strict" => Ok(Strictness::Strict),
+            _ => Err(format!(
+                "неизвестный режим '{s}', допустимы lenient, normal, strict"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug)]
+pub enum Problem {
+    SelfLoop(Arc),
+    DuplicateArc(Vec<Arc>),
+    DuplicateOrder(Vec<Arc>),
+    OrderGap { to: String, missing: Vec<i32> },
+}
+
+#[derive(Debug)]
+pub struct Issue {
+    pub severity: Severity,
+    pub problem: Problem,
+}
+
+fn join_arcs(arcs: &[Arc]) -> String {
+    arcs.iter()
+        .map(|arc| arc.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::SelfLoop(arc) => write!(f, "петля {arc}"),
+            Problem::DuplicateArc(arcs) => {
+                write!(f, "повторяющиеся дуги {}", join_arcs(arcs))
+            }
+            Problem::DuplicateOrder(arcs) => {
+                write!(f, "дуги с одинаковым номером {}", join_arcs(arcs))
+            }
+            Problem::OrderGap { to, missing } => {
+                let missing = missing
+                    .iter()
+                    .map(|order| order.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(
+                    f,
+                    "в нумерации дуг, заходящих в {to}, пропущены {missing}"
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.severity {
+            Severity::Warning => write!(f, "предупреждение: {}", self.problem),
+            Severity::Error => write!(f, "ошибка: {}", self.problem),
+        }
+    }
+}
+
+fn groups<'a, K, F>(arcs: &'a [Arc], key: F) -> Vec<Vec<Arc>>
+where
+    K: std::hash::Hash + Eq,
+    F: Fn(&'a Arc) -> K,
+{
+    let mut index = HashMap::new();
+    let mut groups: Vec<Vec<Arc>> = Vec::new();
+    for arc in arcs {
+        let i = *index.entry(key(arc)).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[i].push(arc.clone());
+    }
+    groups.into_iter().filter(|group| group.len() > 1).collect()
+}
+
+fn order_gaps(arcs: &[Arc]) -> Vec<Problem> {
+    let mut index: HashMap<&String, usize> = HashMap::new();
+    let mut orders: Vec<(&String, Vec<i32>)> = Vec::new();
+    for arc in arcs {
+        let i = *index.entry(&arc.to).or_insert_with(|| {
+            orders.push((&arc.to, Vec::new()));
+            orders.len() - 1
+        });
+        orders[i].1.push(arc.order);
+    }
+    let mut problems = Vec::new();
+    for (to, mut list) in orders {
+        list.sort();
+        list.dedup();
+        let missing: Vec<i32> = list
+            .windows(2)
+            .flat_map(|pair| pair[0] + 1..pair[1])
+            .collect();
+        if !missing.is_empty() {
+            problems.push(Problem::OrderGap {
+                to: to.clone(),
+                missing,
+            });
+        }
+    }
+    problems
+}
+
+impl Graph {
+    pub fn validate(&self, strictness: Strictness) -> Vec<Issue> {
+        let severity = |strict_from| {
+            if strictness >= strict_from {
+                Severity::Error
+            } else {
+                Severity::Warning
+            }
+        };
+        let mut issues = Vec::new();
+        for arc in self.arcs.iter().filter(|arc| arc.from == arc.to) {
+            issues.push(Issue {
+                severity: Severity::Error,
+                problem: Problem::SelfLoop(arc.clone()),
+            });
+        }
+        for arcs in groups(&self.arcs, |arc| (&arc.from, &arc.to)) {
+            issues.push(Issue {
+                severity: severity(Strictness::Normal),
+                problem: Problem::DuplicateArc(arcs),
+            });
+        }
+        for arcs in groups(&self.arcs, |arc| (&arc.to, arc.order)) {
+            issues.push(Issue {
+                severity: severity(Strictness::Normal),
+                problem: Problem::DuplicateOrder(arcs),
+            });
+        }
+        for problem in order_gaps(&self.arcs) {
+            issues.push(Issue {
+                severity: severity(Strictness::Strict),
+                problem,
+            });
+        }
+        issues
+    }
+}
+
+#[derive(Debug)]
+pub struct UndeclaredVertex {
+    pub vertex: String,
+    pub arc: Arc,
+}
+
+#[derive(Debug)]
+pub struct UndeclaredVertices(pub Vec<UndeclaredVertex>);
+
+impl fmt::Display for UndeclaredVertices {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "дуги ссылаются на необъявленные вершины:")?;
+        for reference in &self.0 {
+            let vertex = quote_name(&reference.vertex);
+            write!(f, "\n  {vertex} в дуге {}", reference.arc)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UndeclaredVertices {}
+
+impl Graph {
+    /// Первая вершина, объявленная повторно.
+    pub fn duplicate_vertex(&self) -> Option<&String> {
+        let mut declared = HashSet::new();
+        self.vertices
+            .iter()
+            .find(|vertex| !declared.insert(*vertex))
+    }
+
+    pub fn undeclared_vertices(&self) -> Vec<UndeclaredVertex> {
+        let declared: HashSet<&String> = self.vertices.iter().collect();
+        let mut undeclared = Vec::new();
+        for arc in &self.arcs {
+            for vertex in [&arc.from, &arc.to] {
+                if !declared.contains(vertex) {
+                    undeclared.push(UndeclaredVertex {
+                        vertex: vertex.clone(),
+                        arc: arc.clone(),
+                    });
+                }
+            }
+        }
+        undeclared
+    }
+
+    pub fn check_references(
+        &mut self,
+        auto_declare: bool,
+    ) -> Result<(), UndeclaredVertices> {
+        let undeclared = self.undeclared_vertices();
+        if undeclared.is_empty() {
+            return Ok(());
+        }
+        if !auto_declare {
+            return Err(UndeclaredVertices(undeclared));
+        }
+        for reference in undeclared {
+            if !self.vertices.contains(&reference.vertex) {
+                self.vertices.push(reference.vertex);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(arcs: &[(&str, &str, i32)]) -> Graph {
+        let arcs: Vec<Arc> = arcs
+            .iter()
+            .map(|&(from, to, order)| Arc {
+                from: from.into(),
+                to: to.into(),
+                order,
+            })
+            .collect();
+        let mut vertices: Vec<String> = arcs
+            .iter()
+            .flat_map(|arc| [arc.from.clone(), arc.to.clone()])
+            .collect();
+        vertices.sort();
+        vertices.dedup();
+        Graph { vertices, arcs }
+    }
+
+    fn severities(graph: &Graph, strictness: Strictness) -> Vec<Severity> {
+        graph
+            .validate(strictness)
+            .into_iter()
+            .map(|issue| issue.severity)
+            .collect()
+    }
+
+    #[test]
+    fn valid_graph() {
+        let graph = graph(&[("b", "a", 1), ("c", "a", 2), ("d", "b", 1)]);
+        assert!(graph.validate(Strictness::Strict).is_empty());
+    }
+
+    #[test]
+    fn self_loop_is_always_an_error() {
+        let graph = graph(&[("a", "a", 1)]);
+        let issues = graph.validate(Strictness::Lenient);
+        assert!(matches!(
+            issues[..],
+            [Issue {
+                severity: Severity::Error,
+                problem: Problem::SelfLoop(_),
+            }]
+        ));
+        assert_eq!(issues[0].to_string(), "ошибка: петля (a, a, 1)");
+    }
+
+    #[test]
+    fn duplicate_arc() {
+        let graph = graph(&[("b", "a", 1), ("b", "a", 2)]);
+        let issues = graph.validate(Strictness::Normal);
+        assert!(matches!(&issues[..], [Issue {
+            problem: Problem::DuplicateArc(arcs),
+            ..
+        }] if arcs.len() == 2));
+        assert_eq!(
+            severities(&graph, Strictness::Lenient),
+            [Severity::Warning]
+        );
+        assert_eq!(severities(&graph, Strictness::Normal), [Severity::Error]);
+        assert_eq!(severities(&graph, Strictness::Strict), [Severity::Error]);
+    }
+
+    #[test]
+    fn duplicate_order() {
+        let graph = graph(&[("b", "a", 1), ("c", "a", 1)]);
+        let issues = graph.validate(Strictness::Normal);
+        assert!(matches!(&issues[..], [Issue {
+            problem: Problem::DuplicateOrder(arcs),
+            ..
+        }] if arcs.len() == 2));
+        assert_eq!(
+            issues[0].to_string(),
+            "ошибка: дуги с одинаковым номером (b, a, 1), (c, a, 1)"
+        );
+        assert_eq!(
+            severities(&graph, Strictness::Lenient),
+            [Severity::Warning]
+        );
+        assert_eq!(severities(&graph, Strictness::Normal), [Severity::Error]);
+    }
+
+    #[test]
+    fn order_gap() {
+        let graph = graph(&[("b", "a", 1), ("c", "a", 4)]);
+        let issues = graph.validate(Strictness::Strict);
+        assert!(matches!(&issues[..], [Issue {
+            problem: Problem::OrderGap { to, missing },
+            ..
+        }] if to == "a" && *missing == [2, 3]));
+        assert_eq!(
+            severities(&graph, Strictness::Lenient),
+            [Severity::Warning]
+        );
+        assert_eq!(severities(&graph, Strictness::Normal), [Severity::Warning]);
+        assert_eq!(severities(&graph, Strictness::Strict), [Severity::Error]);
+    }
+
+    #[test]
+    fn strictness_from_str() {
+        assert_eq!("strict".parse(), Ok(Strictness::Strict));
+        assert!("strong".parse::<Strictness>().is_err());
+    }
+
+    #[test]
+    fn undeclared_references() {
+        let mut graph = graph(&[("b", "a", 1), ("c", "x", 1)]);
+        graph
+            .vertices
+            .retain(|vertex| vertex == "a" || vertex == "b");
+        let undeclared: Vec<String> = graph
+            .undeclared_vertices()
+            .into_iter()
+            .map(|reference| reference.vertex)
+            .collect();
+        assert_eq!(undeclared, ["c", "x"]);
+        let err = graph.nodes().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "дуги ссылаются на необъявленные вершины:\n  c в дуге (c, x, 1)\n  \
+             x в дуге (c, x, 1)"
+        );
+        assert!(graph.check_references(false).is_err());
+        assert_eq!(graph.vertices, ["a", "b"]);
+    }
+
+    #[test]
+    fn auto_declare() {
+        let mut graph = graph(&[("b", "a", 1), ("b", "c", 1)]);
+        graph.vertices.retain(|vertex| vertex == "a");
+        graph.check_references(true).unwrap();
+        assert_eq!(graph.vertices, ["a", "b", "c"]);
+        assert_eq!(graph.nodes().unwrap()["b"].children, ["a", "c"]);
+    }
+}
@@ -0,0 +1,707 @@
+use crate::{quote_name, Arc, Graph};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{self, BufReader, Read, Write};
+use std::str::FromStr;
+use xml::attribute::OwnedAttribute;
+use xml::common::{Position, TextPosition, XmlVersion};
+use xml::reader::{EventReader, XmlEvent as ReaderEvent};
+use xml::writer::{
+    EmitterConfig, EventWriter, Result as WriteResult, XmlEvent,
+};
+
+pub const GRAPH_NAMESPACE: &str = "urn:nngraph:graph";
+pub const GRAPH_VERSION: &str = "1";
+
+const XSI_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Ascii,
+}
+
+impl Encoding {
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Ascii => "US-ASCII",
+        }
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "utf-8" | "utf8" => Ok(Encoding::Utf8),
+            "us-ascii" | "ascii" => Ok(Encoding::Ascii),
+            _ => Err(format!(
+                "неизвестная кодировка '{s}', допустимы utf-8, us-ascii"
+            )),
+        }
+    }
+}
+
+/// Настройки записи XML. Значения по умолчанию дают документ с отступами,
+/// без объявления XML и с дугами из вложенных элементов.
+#[derive(Debug, Clone, Default)]
+pub struct XmlOptions {
+    pub compact: bool,
+    pub declaration: bool,
+    pub encoding: Encoding,
+    pub arc_attributes: bool,
+    /// Записать у `<graph>` пространство имён `GRAPH_NAMESPACE`.
+    pub namespace: bool,
+    /// Записать у `<graph>` атрибут `version`.
+    pub version: bool,
+}
+
+fn escape(text: &str, attribute: bool, encoding: Encoding) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if attribute => escaped.push_str("&quot;"),
+            '\n' | '\r' | '\t' if attribute => {
+                escaped.push_str(&format!("&#x{:X};", c as u32))
+            }
+            c if !c.is_ascii() && encoding == Encoding::Ascii => {
+                escaped.push_str(&format!("&#x{:X};", c as u32))
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+struct XmlWriter<'a, T: Write> {
+    writer: EventWriter<T>,
+    options: &'a XmlOptions,
+    labels: Option<(&'a str, &'a HashMap<String, String>)>,
+}
+
+impl<T: Write> XmlWriter<'_, T> {
+    fn text_element(&mut self, name: &str, text: &str) -> WriteResult<()> {
+        let text = escape(text, false, self.options.encoding);
+        self.writer.write(XmlEvent::start_element(name))?;
+        self.writer.write(XmlEvent::characters(&text))?;
+        self.writer.write(XmlEvent::end_element())
+    }
+
+    fn arc(&mut self, arc: &Arc) -> WriteResult<()> {
+        if self.options.arc_attributes {
+            let encoding = self.options.encoding;
+            let from = escape(&arc.from, true, encoding);
+            let to = escape(&arc.to, true, encoding);
+            let order = arc.order.to_string();
+            self.writer.write(
+                XmlEvent::start_element("arc")
+                    .attr("from", &from)
+                    .attr("to", &to)
+                    .attr("order", &order),
+            )?;
+            return self.writer.write(XmlEvent::end_element());
+        }
+        self.writer.write(XmlEvent::start_element("arc"))?;
+        self.text_element("from", &arc.from)?;
+        self.text_element("to", &arc.to)?;
+        self.text_element("order", &arc.order.to_string())?;
+        self.writer.write(XmlEvent::end_element())
+    }
+
+    fn graph(&mut self, graph: &Graph) -> WriteResult<()> {
+        if self.options.declaration {
+            self.writer.write(XmlEvent::StartDocument {
+                version: XmlVersion::Version10,
+                encoding: Some(self.options.encoding.name()),
+                standalone: None,
+            })?;
+        }
+        let mut graph_element = XmlEvent::start_element("graph");
+        if self.options.namespace {
+            graph_element = graph_element.default_ns(GRAPH_NAMESPACE);
+        }
+        if self.options.version {
+            graph_element = graph_element.attr("version", GRAPH_VERSION);
+        }
+        self.writer.write(graph_element)?;
+        for vertex in &graph.vertices {
+            let label = self
+                .labels
+                .and_then(|(name, labels)| Some((name, labels.get(vertex)?)));
+            match label {
+                Some((name, label)) => {
+                    let encoding = self.options.encoding;
+                    let label = escape(label, true, encoding);
+                    let text = escape(vertex, false, encoding);
+                    self.writer.write(
+                        XmlEvent::start_element("vertex").attr(name, &label),
+                    )?;
+                    self.writer.write(XmlEvent::characters(&text))?;
+                    self.writer.write(XmlEvent::end_element())?;
+                }
+                None => self.text_element("vertex", vertex)?,
+            }
+        }
+        for arc in &graph.arcs {
+            self.arc(arc)?;
+        }
+        self.writer.write(XmlEvent::end_element())
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum ParserState {
+    Waiting,
+    Graph,
+    Vertex,
+    Arc,
+    From,
+    To,
+    Order,
+}
+
+impl ParserState {
+    fn holds_text(self) -> bool {
+        matches!(
+            self,
+            ParserState::Vertex
+                | ParserState::From
+                | ParserState::To
+                | ParserState::Order
+        )
+    }
+}
+
+impl Graph {
+    pub fn write_xml<T: Write>(
+        &self,
+        output: T,
+        options: &XmlOptions,
+    ) -> io::Result<()> {
+        self.write_xml_impl(output, options, None)
+    }
+
+    /// Как `write_xml`, но у вершин, для которых есть метка в `labels`,
+    /// она записывается в атрибут `attribute` элемента `<vertex>`.
+    pub fn write_xml_labeled<T: Write>(
+        &self,
+        output: T,
+        options: &XmlOptions,
+        attribute: &str,
+        labels: &HashMap<String, String>,
+    ) -> io::Result<()> {
+        self.write_xml_impl(output, options, Some((attribute, labels)))
+    }
+
+    fn write_xml_impl<T: Write>(
+        &self,
+        output: T,
+        options: &XmlOptions,
+        labels: Option<(&str, &HashMap<String, String>)>,
+    ) -> io::Result<()> {
+        let mut config = EmitterConfig::new()
+            .write_document_declaration(false)
+            .perform_indent(!options.compact)
+            .pad_self_closing(false);
+        config.perform_escaping = false;
+        let writer = config.create_writer(output);
+        XmlWriter {
+            writer,
+            options,
+            labels,
+        }
+        .graph(self)
+        .map_err(io::Error::other)
+    }
+
+    pub fn read_xml<R: Read>(input: R) -> Result<Graph, ReadError> {
+        Graph::read_xml_impl(input, None).map(|(graph, _)| graph)
+    }
+
+    /// Как `read_xml`, но у `<vertex>` допускается атрибут `attribute`.
+    /// Его значения возвращаются по именам вершин.
+    pub fn read_xml_labeled<R: Read>(
+        input: R,
+        attribute: &str,
+    ) -> Result<(Graph, HashMap<String, String>), ReadError> {
+        Graph::read_xml_impl(input, Some(attribute))
+    }
+
+    fn read_xml_impl<R: Read>(
+        input: R,
+        label: Option<&str>,
+    ) -> Result<(Graph, HashMap<String, String>), ReadError> {
+        let mut parser = EventReader::new(BufReader::new(input));
+        let mut graph = Graph::default();
+        let mut state = ParserState::Waiting;
+        let mut found = false;
+        let mut namespace = None;
+        let mut declared = HashSet::new();
+        let mut labels = HashMap::new();
+        let mut vertex_label = None;
+        let mut text = String::new();
+        let mut arc = PartialArc::new(parser.position());
+        let mut element = parser.position();
+
+        loop {
+            let event = parser.next();
+            let position = parser.position();
+            let error = |kind| Err(ReadError::new(kind, position));
+            match event {
+                Ok(ReaderEvent::StartElement {
+                    name, attributes, ..
+                }) => {
+                    if state == ParserState::Waiting
+                        && name.local_name == "graph"
+                        && !found
+                    {
+                        namespace = name.namespace.clone();
+                        if namespace
+                            .as_ref()
+                            .is_some_and(|ns| ns != GRAPH_NAMESPACE)
+                        {
+                            return error(ReadErrorKind::UnexpectedNamespace {
+                                element: name.local_name,
+                                namespace,
+                            });
+                        }
+                        check_graph_attributes(&attributes, position)?;
+                    } else {
+                        if name.namespace != namespace {
+                            return error(ReadErrorKind::UnexpectedNamespace {
+                                element: name.local_name,
+                                namespace: name.namespace,
+                            });
+                        }
+                        let arc_start = state == ParserState::Graph
+                            && name.local_name == "arc";
+                        let vertex_start = state == ParserState::Graph
+                            && name.local_name == "vertex";
+                        let is_label = |attribute: &OwnedAttribute| {
+                            vertex_start
+                                && attribute.name.namespace.is_none()
+                                && Some(attribute.name.local_name.as_str())
+                                    == label
+                        };
+                        vertex_label = attributes
+                            .iter()
+                            .find(|attribute| is_label(attribute))
+                            .map(|attribute| attribute.value.clone());
+                        if let Some(attribute) = attributes
+                            .iter()
+                            .filter(|_| !arc_start)
+                            .find(|attribute| !is_label(attribute))
+                        {
+                            let name = attribute.name.local_name.clone();
+                            return error(ReadErrorKind::UnexpectedAttribute(
+                                name,
+                            ));
+                        }
+                    }
+                    state = match (name.local_name.as_str(), state) {
+                        ("graph", ParserState::Waiting) if !found => {
+                            found = true;
+                            ParserState::Graph
+                        }
+                        ("vertex", ParserState::Graph) => ParserState::Vertex,
+                        ("arc", ParserState::Graph) => {
+                            arc = PartialArc::new(position);
+                            arc.set_attributes(&attributes, position)?;
+                            ParserState::Arc
+                        }
+                        ("from", ParserState::Arc) => ParserState::From,
+                        ("to", ParserState::Arc) => ParserState::To,
+                        ("order", ParserState::Arc) => ParserState::Order,
+                        _ => {
+                            let name = name.local_name;
+                            return error(ReadErrorKind::UnexpectedElement(
+                                name,
+                            ));
+                        }
+                    };
+                    element = position;
+                    text.clear();
+                }
+                Ok(ReaderEvent::EndElement { .. }) => {
+                    state = match state {
+                        ParserState::Graph => ParserState::Waiting,
+                        ParserState::Vertex => {
+                            let vertex =
+                                take_text(&mut text, "vertex", element)?;
+                            if !declared.insert(vertex.clone()) {
+                                return Err(ReadError::new(
+                                    ReadErrorKind::DuplicateVertex(vertex),
+                                    element,
+                                ));
+                            }
+                            if let Some(label) = vertex_label.take() {
+                                labels.insert(vertex.clone(), label);
+                            }
+                            graph.vertices.push(vertex);
+                            ParserState::Graph
+                        }
+                        ParserState::Arc => {
+                            graph.arcs.push(arc.finish()?);
+                            ParserState::Graph
+                        }
+                        ParserState::From => {
+                            let from = take_text(&mut text, "from", element)?;
+                            set_field(&mut arc.from, from, "from", element)?;
+                            ParserState::Arc
+                        }
+                        ParserState::To => {
+                            let to = take_text(&mut text, "to", element)?;
+                            set_field(&mut arc.to, to, "to", element)?;
+                            ParserState::Arc
+                        }
+                        ParserState::Order => {
+                            let order = take_text(&mut text, "order", element)?;
+                            let order = parse_order(order, element)?;
+                            set_field(&mut arc.order, order, "order", element)?;
+                            ParserState::Arc
+                        }
+                        ParserState::Waiting => ParserState::Waiting,
+                    };
+                }
+                Ok(ReaderEvent::Characters(chars))
+                | Ok(ReaderEvent::CData(chars)) => {
+                    if state.holds_text() {
+                        text.push_str(&chars);
+                    } else if !chars.trim().is_empty() {
+                        let chars = chars.trim().to_string();
+                        return error(ReadErrorKind::UnexpectedText(chars));
+                    }
+                }
+                Ok(ReaderEvent::Whitespace(chars)) => {
+                    if state.holds_text() {
+                        text.push_str(&chars);
+                    }
+                }
+                Ok(ReaderEvent::EndDocument) => break,
+                Ok(_) => {}
+                Err(err) => {
+                    return Err(ReadError::new(
+                        ReadErrorKind::Syntax(err.msg().to_string()),
+                        err.position(),
+                    ))
+                }
+            }
+        }
+
+        if !found {
+            return Err(ReadError::new(
+                ReadErrorKind::MissingGraph,
+                parser.position(),
+            ));
+        }
+        Ok((graph, labels))
+    }
+}
+
+#[derive(Debug)]
+pub enum ReadErrorKind {
+    Syntax(String),
+    MissingGraph,
+    UnexpectedElement(String),
+    UnexpectedAttribute(String),
+    UnexpectedText(String),
+    EmptyElement(&'static str),
+    MissingField(&'static str),
+    DuplicateField(&'static str),
+    InvalidOrder(String),
+    MissingAttribute {
+        element: &'static str,
+        attribute: &'static str,
+    },
+    UnexpectedNamespace {
+        element: String,
+        namespace: Option<String>,
+    },
+    UnsupportedVersion(String),
+    DuplicateVertex(String),
+}
+
+impl fmt::Display for ReadErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadErrorKind::Syntax(msg) => write!(f, "{msg}"),
+            ReadErrorKind::MissingGraph => {
+                write!(f, "в документе нет элемента <graph>")
+            }
+            ReadErrorKind::UnexpectedElement(name) => {
+                write!(f, "неожиданный элемент <{name}>")
+            }
+            ReadErrorKind::UnexpectedAttribute(name) => {
+                write!(f, "неожиданный атрибут {name}")
+            }
+            ReadErrorKind::UnexpectedText(text) => {
+                write!(f, "неожиданный текст '{text}'")
+            }
+            ReadErrorKind::EmptyElement(name) => {
+                write!(f, "пустой элемент <{name}>")
+            }
+            ReadErrorKind::MissingField(field) => {
+                write!(f, "в дуге нет элемента <{field}>")
+            }
+            ReadErrorKind::DuplicateField(field) => {
+                write!(f, "в дуге больше одного элемента <{field}>")
+            }
+            ReadErrorKind::InvalidOrder(order) => {
+                write!(f, "порядковый номер '{order}' не является целым числом")
+            }
+            ReadErrorKind::MissingAttribute { element, attribute } => {
+                write!(f, "у элемента <{element}> нет атрибута {attribute}")
+            }
+            ReadErrorKind::UnexpectedNamespace {
+                element,
+                namespace: Some(namespace),
+            } => write!(
+                f,
+                "элемент <{element}> из неизвестного пространства имён \
+                 '{namespace}'"
+            ),
+            ReadErrorKind::UnexpectedNamespace {
+                element,
+                namespace: None,
+            } => write!(
+                f,
+                "элемент <{element}> не находится в пространстве имён графа"
+            ),
+            ReadErrorKind::UnsupportedVersion(version) => write!(
+                f,
+                "версия формата '{version}' не поддерживается, ожидалась \
+                 {GRAPH_VERSION}"
+            ),
+            ReadErrorKind::DuplicateVertex(vertex) => {
+                write!(f, "вершина {} объявлена повторно", quote_name(vertex))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ReadError {
+    pub kind: ReadErrorKind,
+    pub position: TextPosition,
+}
+
+impl ReadError {
+    pub(crate) fn new(
+        kind: ReadErrorKind,
+        position: TextPosition,
+    ) -> ReadError {
+        ReadError { kind, position }
+    }
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "строка {}, столбец {}: {}",
+            self.position.row + 1,
+            self.position.column + 1,
+            self.kind
+        )
+    }
+}
+
+impl std::error::Error for ReadError {}
+
+struct PartialArc {
+    from: Option<String>,
+    to: Option<String>,
+    order: Option<i32>,
+    position: TextPosition,
+}
+
+impl PartialArc {
+    fn new(position: TextPosition) -> PartialArc {
+        PartialArc {
+            from: None,
+            to: None,
+            order: None,
+            position,
+        }
+    }
+
+    fn set_attributes(
+        &mut self,
+        attributes: &[OwnedAttribute],
+        position: TextPosition,
+    ) -> Result<(), ReadError> {
+        for attribute in attributes {
+            let name = &attribute.name;
+            let mut value = attribute.value.clone();
+            match (name.namespace.as_ref(), name.local_name.as_str()) {
+                (None, "from") => {
+                    let from = take_text(&mut value, "from", position)?;
+                    set_field(&mut self.from, from, "from", position)?;
+                }
+                (None, "to") => {
+                    let to = take_text(&mut value, "to", position)?;
+                    set_field(&mut self.to, to, "to", position)?;
+                }
+                (None, "order") => {
+                    let order = take_text(&mut value, "order", position)?;
+                    let order = parse_order(order, position)?;
+                    set_field(&mut self.order, order, "order", position)?;
+                }
+                _ => {
+                    let kind = ReadErrorKind::UnexpectedAttribute(
+                        name.local_name.clone(),
+                    );
+                    return Err(ReadError::new(kind, position));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<Arc, ReadError> {
+        let missing = |field| {
+            ReadError::new(ReadErrorKind::MissingField(field), self.position)
+        };
+        Ok(Arc {
+            from: self.from.take().ok_or_else(|| missing("from"))?,
+            to: self.to.take().ok_or_else(|| missing("to"))?,
+            order: self.order.take().ok_or_else(|| missing("order"))?,
+        })
+    }
+}
+
+fn check_graph_attributes(
+    attributes: &[OwnedAttribute],
+    position: TextPosition,
+) -> Result<(), ReadError> {
+    for attribute in attributes {
+        let name = &attribute.name;
+        if name.namespace.as_deref() == Some(XSI_NAMESPACE) {
+            continue;
+        }
+        let kind = if name.namespace.is_none() && name.local_name == "version" {
+            if attribute.value.trim() == GRAPH_VERSION {
+                continue;
+            }
+            ReadErrorKind::UnsupportedVersion(attribute.value.clone())
+        } else {
+            ReadErrorKind::UnexpectedAttribute(name.local_name.clone())
+        };
+        return Err(ReadError::new(kind, position));
+    }
+    Ok(())
+}
+
+fn parse_order(
+    order: String,
+    position: TextPosition,
+) -> Result<i32, ReadError> {
+    match order.trim().parse::<i32>() {
+        Ok(order) => Ok(order),
+        Err(_) => {
+            Err(ReadError::new(ReadErrorKind::InvalidOrder(order), position))
+        }
+    }
+}
+
+fn take_text(
+    text: &mut String,
+    name: &'static str,
+    position: TextPosition,
+) -> Result<String, ReadError> {
+    if text.is_empty() {
+        let kind = ReadErrorKind::EmptyElement(name);
+        return Err(ReadError::new(kind, position));
+    }
+    Ok(std::mem::take(text))
+}
+
+fn set_field<T>(
+    field: &mut Option<T>,
+    value: T,
+    name: &'static str,
+    position: TextPosition,
+) -> Result<(), ReadError> {
+    if field.is_some() {
+        let kind = ReadErrorKind::DuplicateField(name);
+        return Err(ReadError::new(kind, position));
+    }
+    *field = Some(value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(input: &str) -> ReadError {
+        Graph::read_xml(input.as_bytes()).unwrap_err()
+    }
+
+    fn position(err: &ReadError) -> (u64, u64) {
+        (err.position.row, err.position.column)
+    }
+
+    #[test]
+    fn comments_instructions_and_cdata() {
+        let input = "<?pi x?><graph><!-- c --><vertex><![CDATA[a]]>b</vertex>\
+                     <vertex>c</vertex><arc><from>ab</from><to>c</to>\
+                     <order> 1 </order></arc></graph>";
+        let graph = Graph::read_xml(input.as_bytes()).unwrap();
+        assert_eq!(graph.vertices, ["ab", "c"]);
+        assert_eq!(graph.arcs[0].order, 1);
+    }
+
+    #[test]
+    fn invalid_order_position() {
+        let err = error(
+            "<graph>\n  <arc>\n    <from>a</from>\n    <to>b</to>\n    \
+             <order>x</order>\n  </arc>\n</graph>",
+        );
+        assert_eq!(position(&err), (4, 4));
+        assert_eq!(
+            err.to_string(),
+            "строка 5, столбец 5: порядковый номер 'x' не является целым числом"
+        );
+    }
+
+    #[test]
+    fn unexpected_content() {
+        let err = error("<graph><node/></graph>");
+        assert_eq!(err.kind.to_string(), "неожиданный элемент <node>");
+        assert_eq!(position(&err), (0, 7));
+        let err = error(r#"<graph><vertex id="a">a</vertex></graph>"#);
+        assert_eq!(err.kind.to_string(), "неожиданный атрибут id");
+        let err = error("<graph>x<vertex>a</vertex></graph>");
+        assert_eq!(err.kind.to_string(), "неожиданный текст 'x'");
+    }
+
+    #[test]
+    fn arc_fields() {
+        let err = error("<graph><vertex></vertex></graph>");
+        assert!(matches!(err.kind, ReadErrorKind::EmptyElement("vertex")));
+        let err = error("<graph>\n<arc><from>a</from><to>b</to></arc></graph>");
+        assert!(matches!(err.kind, ReadErrorKind::MissingField("order")));
+        assert_eq!(position(&err), (1, 0));
+        let err = error(
+            "<graph><arc><from>a</from><from>b</from><to>b</to>\
+             <order>1</order></arc></graph>",
+        );
+        assert!(matches!(err.kind, ReadErrorKind::DuplicateField("from")));
+        assert_eq!(position(&err), (0, 26));
+    }
+
+    #[test]
+    fn malformed_xml() {
+        let err = error("<graph><vertex>a</graph>");
+        assert!(matches!(err.kind, ReadErrorKind::Syntax(_)));
+        let err = error("<other/>");
+        assert_eq!(err.kind.to_string(), "неожиданный элемент <other>");
+    }
+}
@@ -0,0 +1,40 @@
+//! Запуски nntask1 на файлах из каталога `tests` в корне репозитория с
+//! проверкой вывода по ожидаемым файлам.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests")
+        .join(name)
+}
+
+/// Запускает nntask1 и сравнивает записанный граф с файлом `expected`.
+fn check(input: &str, expected: &str) {
+    let output = Path::new(env!("CARGO_TARGET_TMPDIR")).join(expected);
+    let _ = fs::remove_file(&output);
+    let result = Command::new(env!("CARGO_BIN_EXE_nntask1"))
+        .arg("--input1")
+        .arg(fixture(input))
+        .arg("--output1")
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8(result.stdout).unwrap(), "");
+    assert_eq!(
+        fs::read_to_string(output).unwrap(),
+        fs::read_to_string(fixture(expected)).unwrap()
+    );
+}
+
+#[test]
+fn arc_list() {
+    check("t1_input.txt", "t1_output.xml");
+}
+
+#[test]
+fn implicit_orders() {
+    check("t1_input_implicit.txt", "t1_output_implicit.xml");
+}
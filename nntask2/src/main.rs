@@ -1,178 +1,56 @@
+mod render;
+
 use clap::Parser;
-use std::collections::{HashMap, HashSet};
-use std::fs::File;
+use nngraph::{find_roots, Graph, GraphFormat, LoadError, UndeclaredVertices};
+use render::{call_string, shared_call_string, Format};
+use std::fmt;
+use std::fs::{self, File};
 use std::io::Write;
-use xml::reader::{EventReader, XmlEvent};
-
-#[derive(Debug, Clone)]
-struct Arc {
-    from: String,
-    to: String,
-    order: i32,
-}
-
-#[derive(Debug)]
-struct Graph {
-    vertices: Vec<String>,
-    arcs: Vec<Arc>,
-}
 
-#[derive(PartialEq, Eq)]
-enum ParserState {
-    Waiting,
-    Graph,
-    Vertex,
-    Arc,
-    From,
-    To,
-    Order,
+enum CallError {
+    Undeclared(UndeclaredVertices),
+    EmptyGraph,
+    UnknownRoot(String),
 }
 
-fn parse_input(input_file: &File) -> Option<Graph> {
-    let mut vertices = Vec::new();
-    let mut arcs = Vec::new();
-    let mut arc = Arc {
-        from: String::new(),
-        to: String::new(),
-        order: 0,
-    };
-    let mut state = ParserState::Waiting;
-    let parser = EventReader::new(input_file);
-
-    for e in parser {
-        match e {
-            Ok(XmlEvent::StartElement { name, .. }) => {
-                state = match (name.local_name.as_str(), state) {
-                    ("graph", ParserState::Waiting) => ParserState::Graph,
-                    ("vertex", ParserState::Graph) => ParserState::Vertex,
-                    ("arc", ParserState::Graph) => ParserState::Arc,
-                    ("from", ParserState::Arc) => ParserState::From,
-                    ("to", ParserState::Arc) => ParserState::To,
-                    ("order", ParserState::Arc) => ParserState::Order,
-                    _ => return None,
-                };
-            }
-            Ok(XmlEvent::EndElement { name, .. }) => {
-                state = match (name.local_name.as_str(), state) {
-                    ("graph", ParserState::Graph) => ParserState::Waiting,
-                    ("vertex", ParserState::Vertex) => ParserState::Graph,
-                    ("arc", ParserState::Arc) => {
-                        arcs.push(arc.clone());
-                        ParserState::Graph
-                    }
-                    ("from", ParserState::From) => ParserState::Arc,
-                    ("to", ParserState::To) => ParserState::Arc,
-                    ("order", ParserState::Order) => ParserState::Arc,
-                    _ => return None,
-                };
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CallError::Undeclared(err) => write!(f, "{err}"),
+            CallError::EmptyGraph => write!(f, "граф не содержит вершин"),
+            CallError::UnknownRoot(name) => {
+                write!(f, "в графе нет вершины {name}")
             }
-            Ok(XmlEvent::Characters(text)) => match state {
-                ParserState::Vertex => vertices.push(text),
-                ParserState::From => arc.from = text,
-                ParserState::To => arc.to = text,
-                ParserState::Order => arc.order = text.parse::<i32>().unwrap(),
-                _ => return None,
-            },
-            Err(err) => println!("{err}"),
-            _ => {}
         }
     }
-
-    Some(Graph { vertices, arcs })
-}
-
-#[derive(Debug)]
-struct Node {
-    name: String,
-    children: Vec<String>,
-    parents: Vec<String>,
 }
 
-impl Node {
-    fn new(name: String) -> Node {
-        Node {
-            name,
-            children: Vec::new(),
-            parents: Vec::new(),
-        }
+fn get_call_strings(
+    g: &Graph,
+    root: Option<&String>,
+    share: bool,
+    format: Format,
+) -> Result<Vec<String>, CallError> {
+    let nodes = g.nodes().map_err(CallError::Undeclared)?;
+    if nodes.is_empty() {
+        return Err(CallError::EmptyGraph);
     }
-}
+    let roots = match root {
+        Some(root) if nodes.contains_key(root) => vec![root.clone()],
+        Some(root) => return Err(CallError::UnknownRoot(root.clone())),
+        None => find_roots(&nodes),
+    };
 
-fn call_string(node: &Node, nodes: &HashMap<String, Node>) -> String {
-    let parents = node
-        .parents
+    Ok(roots
         .iter()
-        .map(|c| call_string(&nodes[c], nodes))
-        .collect::<Vec<String>>();
-    format!("{}({})", node.name, parents.join(", "))
-}
-
-fn find_root(nodes: &HashMap<String, Node>) -> Option<String> {
-    let mut root_name = String::new();
-    let mut found = false;
-    for (name, node) in nodes.iter() {
-        if node.children.is_empty() {
-            if found {
-                return None;
+        .map(|root| {
+            if share {
+                shared_call_string(root, &nodes, format)
+            } else {
+                call_string(root, &nodes, format)
             }
-            root_name = name.clone();
-            found = true;
-        }
-    }
-    Some(root_name)
-}
-
-fn has_cycle(
-    root: &String,
-    name: &String,
-    visited: &mut HashSet<String>,
-    nodes: &HashMap<String, Node>,
-) -> bool {
-    if visited.contains(name) {
-        return false;
-    }
-    visited.insert(name.clone());
-    for node in &nodes[name].parents {
-        if node == root {
-            return true;
-        }
-        if has_cycle(root, node, visited, nodes) {
-            return true;
-        }
-    }
-    false
-}
-
-fn get_call_string(g: &Graph) -> Option<String> {
-    let mut nodes = HashMap::new();
-    for vert in &g.vertices {
-        nodes.insert(vert.clone(), Node::new(vert.clone()));
-    }
-    for arc in &g.arcs {
-        nodes
-            .get_mut(&arc.from)
-            .unwrap()
-            .children
-            .push(arc.to.clone());
-        nodes
-            .get_mut(&arc.to)
-            .unwrap()
-            .parents
-            .push(arc.from.clone());
-    }
-
-    let mut visited = HashSet::new();
-    for vert in &g.vertices {
-        if has_cycle(vert, vert, &mut visited, &nodes) {
-            return None;
-        }
-        visited.clear();
-    }
-
-    let root_name = find_root(&nodes)?;
-    let root_node = &nodes[&root_name];
-
-    Some(call_string(root_node, &nodes))
+        })
+        .collect())
 }
 
 #[derive(Parser)]
@@ -181,39 +59,112 @@ struct Config {
     input1: String,
     #[arg(long, value_name = "FILE")]
     output1: String,
+    #[arg(long, help = "Объявить вершины, на которые ссылаются дуги")]
+    auto_declare: bool,
+    #[arg(
+        long,
+        value_name = "VERTEX",
+        help = "Вывести функцию только для указанной вершины"
+    )]
+    root: Option<String>,
+    #[arg(long, help = "Выносить общие подвыражения в let-связывания")]
+    share: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Format::Call,
+        help = "Формат вывода"
+    )]
+    format: Format,
+    #[arg(
+        long,
+        conflicts_with_all = ["auto_declare", "root", "share", "format"],
+        help = "Построить граф в формате XML по строке вызова функции"
+    )]
+    reverse: bool,
 }
 
-fn main() {
-    let config = Config::parse();
-    let input = match File::open(config.input1) {
+fn reverse(config: &Config) {
+    let input = match fs::read_to_string(&config.input1) {
         Ok(s) => s,
         Err(_) => {
-            println!("Не удалось прочитать граф из указанного файла");
+            println!("Не удалось прочитать функцию из указанного файла");
             return;
         }
     };
-    let g = match parse_input(&input) {
-        Some(g) => g,
-        None => {
-            println!("Некорректный ввод");
+    let g = match Graph::from_call_string(&input) {
+        Ok(g) => g,
+        Err(err) => {
+            println!("Некорректный ввод: {err}");
             return;
         }
     };
-    let mut output = match File::create(config.output1) {
+    let cycles = g.cycles();
+    if !cycles.is_empty() {
+        println!("Некорректный ввод - в графе есть циклы:");
+        for cycle in cycles {
+            println!("  {cycle}");
+        }
+        return;
+    }
+    let output = match File::create(&config.output1) {
         Ok(out) => out,
         Err(_) => {
             println!("Не удалось создать файл для вывода");
             return;
         }
     };
-    let call_string = match get_call_string(&g) {
-        Some(s) => s,
-        None => {
-            println!("Некорректный ввод - в графе есть циклы");
+    let format = GraphFormat::from_path(&config.output1).unwrap_or(GraphFormat::Xml);
+    if let Err(err) = g.write(output, format) {
+        println!("{err}");
+    }
+}
+
+fn main() {
+    let config = Config::parse();
+    if config.reverse {
+        reverse(&config);
+        return;
+    }
+    let mut g = match Graph::load(&config.input1) {
+        Ok(g) => g,
+        Err(LoadError::Io(_)) => {
+            println!("Не удалось прочитать граф из указанного файла");
+            return;
+        }
+        Err(err) => {
+            println!("Некорректный ввод: {err}");
+            return;
+        }
+    };
+    if let Err(err) = g.check_references(config.auto_declare) {
+        println!("Некорректный ввод: {err}");
+        return;
+    }
+    let cycles = g.cycles();
+    if !cycles.is_empty() {
+        println!("Некорректный ввод - в графе есть циклы:");
+        for cycle in cycles {
+            println!("  {cycle}");
+        }
+        return;
+    }
+    let call_strings = match get_call_strings(&g, config.root.as_ref(), config.share, config.format)
+    {
+        Ok(s) => s,
+        Err(err) => {
+            println!("Некорректный ввод - {err}");
+            return;
+        }
+    };
+    let mut output = match File::create(&config.output1) {
+        Ok(out) => out,
+        Err(_) => {
+            println!("Не удалось создать файл для вывода");
             return;
         }
     };
     output
-        .write_all(call_string.as_bytes())
+        .write_all(config.format.join(call_strings).as_bytes())
         .expect("Не удалось записать вывод в файл");
 }
@@ -0,0 +1,221 @@
+use clap::ValueEnum;
+use nngraph::{quote_name, topological_order, Node};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Строка вызова функции: a(b(), c())
+    Call,
+    /// S-выражение: (a (b) (c))
+    Sexpr,
+    /// JSON: массив [{"name":"a","args":[...]}] с выражением для каждого корня
+    Json,
+    /// Дерево с отступами
+    Tree,
+}
+
+/// Выражение вершины в общем списке выражений: вызов с номерами
+/// выражений аргументов или ссылка на общее подвыражение. Аргумент,
+/// используемый несколько раз, хранится один раз.
+enum Term {
+    Call(String, Vec<usize>),
+    Ref(String),
+}
+
+/// Часть записи, которую ещё предстоит вывести.
+enum Step<'a> {
+    Term(usize),
+    Text(&'a str),
+}
+
+impl Format {
+    /// Записывает выражение `root`. Обход идёт по явному стеку, поэтому
+    /// глубина выражения не ограничена размером стека, а каждая часть
+    /// записи копируется в результат один раз.
+    fn write(self, terms: &[Term], root: usize) -> String {
+        if self == Format::Tree {
+            return tree(terms, root);
+        }
+        let mut result = String::new();
+        let mut stack = vec![Step::Term(root)];
+        while let Some(step) = stack.pop() {
+            let (name, args) = match step {
+                Step::Text(text) => {
+                    result.push_str(text);
+                    continue;
+                }
+                Step::Term(i) => match &terms[i] {
+                    Term::Ref(name) => {
+                        result.push_str(&self.reference(name));
+                        continue;
+                    }
+                    Term::Call(name, args) => (name, args),
+                },
+            };
+            let (separator, close) = match self {
+                Format::Sexpr => {
+                    result.push('(');
+                    result.push_str(&quote_name(name));
+                    if !args.is_empty() {
+                        result.push(' ');
+                    }
+                    (" ", ")")
+                }
+                Format::Json => {
+                    result.push_str("{\"name\":");
+                    result.push_str(&json_string(name));
+                    result.push_str(",\"args\":[");
+                    (",", "]}")
+                }
+                _ => {
+                    result.push_str(&quote_name(name));
+                    result.push('(');
+                    (", ", ")")
+                }
+            };
+            stack.push(Step::Text(close));
+            for (k, &arg) in args.iter().enumerate().rev() {
+                stack.push(Step::Term(arg));
+                if k > 0 {
+                    stack.push(Step::Text(separator));
+                }
+            }
+        }
+        result
+    }
+
+    fn reference(self, name: &str) -> String {
+        match self {
+            Format::Json => format!("{{\"ref\":{}}}", json_string(name)),
+            _ => quote_name(name).into_owned(),
+        }
+    }
+
+    fn bind(self, bound: Vec<(String, String)>, body: String) -> String {
+        if bound.is_empty() {
+            return body;
+        }
+        match self {
+            Format::Call => {
+                let mut result = String::new();
+                for (name, expression) in bound {
+                    let name = quote_name(&name);
+                    result.push_str(&format!("let {name} = {expression} in "));
+                }
+                result + &body
+            }
+            Format::Sexpr => {
+                let bound = bound
+                    .iter()
+                    .map(|(name, expression)| format!("({} {expression})", quote_name(name)))
+                    .collect::<Vec<String>>();
+                format!("(let* ({}) {body})", bound.join(" "))
+            }
+            Format::Json => {
+                let bound = bound
+                    .iter()
+                    .map(|(name, expression)| {
+                        let name = json_string(name);
+                        format!("{{\"name\":{name},\"value\":{expression}}}")
+                    })
+                    .collect::<Vec<String>>();
+                format!("{{\"let\":[{}],\"in\":{body}}}", bound.join(","))
+            }
+            Format::Tree => {
+                let mut trees = bound
+                    .into_iter()
+                    .map(|(name, expression)| format!("{} =\n{expression}", quote_name(&name)))
+                    .collect::<Vec<String>>();
+                trees.push(body);
+                trees.join("\n\n")
+            }
+        }
+    }
+
+    /// Объединяет выражения для разных корней графа. JSON всегда
+    /// записывается массивом, даже для одного корня, чтобы вывод оставался
+    /// одним документом одного вида.
+    pub fn join(self, expressions: Vec<String>) -> String {
+        match self {
+            Format::Json => {
+                format!("[{}]", expressions.join(","))
+            }
+            Format::Tree => expressions.join("\n\n"),
+            _ => expressions.join("\n"),
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    serde_json::Value::from(s).to_string()
+}
+
+/// Дерево с отступами: каждый аргумент на своей строке под вызовом.
+/// Ссылка на общее подвыражение `c` записывается как `= c`, чтобы её
+/// нельзя было спутать с вершиной `c` без аргументов.
+fn tree(terms: &[Term], root: usize) -> String {
+    let mut lines = Vec::new();
+    let mut stack = vec![(root, String::new(), String::new())];
+    while let Some((i, first, rest)) = stack.pop() {
+        match &terms[i] {
+            Term::Ref(name) => lines.push(format!("{first}= {}", quote_name(name))),
+            Term::Call(name, args) => {
+                lines.push(format!("{first}{}", quote_name(name)));
+                for (k, &arg) in args.iter().enumerate().rev() {
+                    let (branch, indent) = if k + 1 == args.len() {
+                        ("└── ", "    ")
+                    } else {
+                        ("├── ", "│   ")
+                    };
+                    stack.push((arg, format!("{rest}{branch}"), format!("{rest}{indent}")));
+                }
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+/// Выражения строятся снизу вверх в порядке `topological_order`, без
+/// рекурсии. С `share` вершины, используемые несколькими дугами,
+/// записываются отдельно и заменяются ссылками. Возвращает записи таких
+/// вершин и запись `root`.
+fn expressions(
+    root: &str,
+    nodes: &HashMap<String, Node>,
+    format: Format,
+    share: bool,
+) -> (Vec<(String, String)>, String) {
+    let order = topological_order(root, nodes);
+    let mut uses: HashMap<&str, usize> = HashMap::new();
+    for name in &order {
+        for parent in &nodes[name].parents {
+            *uses.entry(parent).or_default() += 1;
+        }
+    }
+    let mut bound = Vec::new();
+    let mut terms = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for name in &order {
+        let args = nodes[name]
+            .parents
+            .iter()
+            .map(|parent| index[parent.as_str()])
+            .collect();
+        terms.push(Term::Call(name.clone(), args));
+        if share && uses.get(name.as_str()).is_some_and(|&n| n > 1) {
+            bound.push((name.clone(), format.write(&terms, terms.len() - 1)));
+            terms.push(Term::Ref(name.clone()));
+        }
+        index.insert(name, terms.len() - 1);
+    }
+    (bound, format.write(&terms, index[root]))
+}
+
+pub fn call_string(root: &str, nodes: &HashMap<String, Node>, format: Format) -> String {
+    expressions(root, nodes, format, false).1
+}
+
+pub fn shared_call_string(root: &str, nodes: &HashMap<String, Node>, format: Format) -> String {
+    let (bound, body) = expressions(root, nodes, format, true);
+    format.bind(bound, body)
+}
@@ -0,0 +1,143 @@
+//! Запуски nntask2 на файлах из каталога `tests` в корне репозитория с
+//! проверкой вывода по ожидаемым файлам.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests")
+        .join(name)
+}
+
+fn read_fixture(name: &str) -> String {
+    fs::read_to_string(fixture(name)).unwrap()
+}
+
+/// Запускает nntask2 с `--input1 input --output1 output`, возвращает
+/// стандартный вывод и содержимое `output`.
+fn run(input: &Path, output: &str, args: &[&str]) -> (String, String) {
+    let output = Path::new(env!("CARGO_TARGET_TMPDIR")).join(output);
+    let _ = fs::remove_file(&output);
+    let result = Command::new(env!("CARGO_BIN_EXE_nntask2"))
+        .arg("--input1")
+        .arg(input)
+        .arg("--output1")
+        .arg(&output)
+        .args(args)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(result.stdout).unwrap();
+    (stdout, fs::read_to_string(output).unwrap_or_default())
+}
+
+#[test]
+fn call_string() {
+    let (stdout, output) = run(&fixture("t1_output.xml"), "t2_call.txt", &[]);
+    assert_eq!(stdout, "");
+    assert_eq!(output, "a(b(f()), c(d(), e()))");
+}
+
+#[test]
+fn cycle_message() {
+    let (stdout, output) = run(&fixture("t2_input_cycle.xml"), "t2_cycle.txt", &[]);
+    assert_eq!(stdout, read_fixture("t2_output_cycle.txt"));
+    assert_eq!(output, "");
+}
+
+#[test]
+fn reverse_round_trip() {
+    let (_, graph) = run(&fixture("t2_output.txt"), "t2_reverse.xml", &["--reverse"]);
+    assert_eq!(graph, read_fixture("t2_reverse.xml"));
+    let reversed = Path::new(env!("CARGO_TARGET_TMPDIR")).join("t2_reverse.xml");
+    let (_, output) = run(&reversed, "t2_round_trip.txt", &[]);
+    assert_eq!(output, read_fixture("t2_output.txt"));
+}
+
+#[test]
+fn shared_call_string() {
+    let input = fixture("t2_input_shared.xml");
+    let (_, output) = run(&input, "t2_shared.txt", &["--share"]);
+    assert_eq!(output, read_fixture("t2_output_shared.txt"));
+    let (_, output) = run(
+        &input,
+        "t2_shared_tree.txt",
+        &["--share", "--format", "tree"],
+    );
+    assert_eq!(output, read_fixture("t2_output_shared_tree.txt"));
+}
+
+#[test]
+fn dot_labels() {
+    let (stdout, output) = run(&fixture("t2_input.dot"), "t2_dot.txt", &[]);
+    assert_eq!(stdout, "");
+    assert_eq!(output, read_fixture("t2_output_dot.txt"));
+}
+
+#[test]
+fn json_roots_array() {
+    let (_, output) = run(
+        &fixture("t2_input_roots.xml"),
+        "t2_roots.json",
+        &["--format", "json"],
+    );
+    assert_eq!(output, read_fixture("t2_output_roots.json"));
+    let roots: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(roots.as_array().map(Vec::len), Some(2));
+}
+
+/// Цепочка из `length` вершин: `v0` — корень, `v{i + 1}` — аргумент `v{i}`.
+fn chain(length: usize) -> PathBuf {
+    let mut xml = String::from("<graph>");
+    for i in 0..length {
+        xml.push_str(&format!("<vertex>v{i}</vertex>"));
+    }
+    for i in 1..length {
+        let to = i - 1;
+        xml.push_str(&format!(
+            "<arc><from>v{i}</from><to>v{to}</to><order>1</order></arc>"
+        ));
+    }
+    xml.push_str("</graph>");
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("t2_chain.xml");
+    fs::write(&path, xml).unwrap();
+    path
+}
+
+#[test]
+fn deep_chain() {
+    let input = chain(100_000);
+    for args in [&[][..], &["--share"]] {
+        let (stdout, output) = run(&input, "t2_chain.txt", args);
+        assert_eq!(stdout, "");
+        assert!(output.starts_with("v0(v1(v2("));
+        let end = format!("v99999({}", ")".repeat(100_000));
+        assert!(output.ends_with(&end));
+    }
+}
+
+#[test]
+fn json_single_root_array() {
+    let (_, output) = run(
+        &fixture("t1_output.xml"),
+        "t2_single.json",
+        &["--format", "json"],
+    );
+    let roots: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(roots.as_array().map(Vec::len), Some(1));
+    assert_eq!(roots[0]["name"], "a");
+}
+
+#[test]
+fn keyword_names_round_trip() {
+    let input = fixture("t2_input_keywords.xml");
+    let (_, output) = run(&input, "t2_keywords.txt", &["--share"]);
+    assert_eq!(output, read_fixture("t2_output_keywords.txt"));
+    let shared = Path::new(env!("CARGO_TARGET_TMPDIR")).join("t2_keywords.txt");
+    let (stdout, _) = run(&shared, "t2_keywords.xml", &["--reverse"]);
+    assert_eq!(stdout, "");
+    let reversed = Path::new(env!("CARGO_TARGET_TMPDIR")).join("t2_keywords.xml");
+    let (_, output) = run(&reversed, "t2_keywords_round_trip.txt", &["--share"]);
+    assert_eq!(output, read_fixture("t2_output_keywords.txt"));
+}
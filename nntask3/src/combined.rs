@@ -0,0 +1,146 @@
+use crate::operation::Operation;
+use nngraph::{quote_name, Arc, Graph, GraphFormat, ReadError, XmlOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+
+/// Атрибут `<vertex>`, в котором хранится операция вершины.
+const OPERATION_ATTRIBUTE: &str = "op";
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Node {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    op: Option<Operation>,
+}
+
+/// Граф вместе с операциями в JSON: `{"nodes": [{"name": "b", "op":
+/// "exp"}], "edges": [{"from": "f", "to": "b", "order": 1}]}`.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Document {
+    nodes: Vec<Node>,
+    edges: Vec<Arc>,
+}
+
+#[derive(Debug)]
+pub enum CombinedError {
+    Io(io::Error),
+    Xml(ReadError),
+    Json(serde_json::Error),
+    Operation { vertex: String, message: String },
+    DuplicateVertex(String),
+    UnsupportedFormat(GraphFormat),
+}
+
+impl fmt::Display for CombinedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CombinedError::Io(err) => write!(f, "{err}"),
+            CombinedError::Xml(err) => write!(f, "{err}"),
+            CombinedError::Json(err) => write!(f, "{err}"),
+            CombinedError::Operation { vertex, message } => {
+                write!(f, "вершина {}: {message}", quote_name(vertex))
+            }
+            CombinedError::DuplicateVertex(vertex) => {
+                write!(f, "вершина {} объявлена повторно", quote_name(vertex))
+            }
+            CombinedError::UnsupportedFormat(format) => write!(
+                f,
+                "формат {format:?} не может хранить операции, граф с \
+                 операциями хранится только в XML и JSON"
+            ),
+        }
+    }
+}
+
+/// Читает граф, в котором операции записаны прямо у вершин: в XML атрибутом
+/// `<vertex op="exp">b</vertex>`, в JSON полем `op` элемента `nodes`.
+/// Вершина без операции допустима, ошибка будет при вычислении.
+pub fn read_combined(
+    input: &str,
+    format: GraphFormat,
+) -> Result<(Graph, HashMap<String, Operation>), CombinedError> {
+    let mut operations = HashMap::new();
+    match format {
+        GraphFormat::Xml => {
+            let (graph, labels) =
+                Graph::read_xml_labeled(input.as_bytes(), OPERATION_ATTRIBUTE)
+                    .map_err(CombinedError::Xml)?;
+            for vertex in &graph.vertices {
+                let Some(label) = labels.get(vertex) else {
+                    continue;
+                };
+                let operation = label.parse().map_err(|message| {
+                    CombinedError::Operation {
+                        vertex: vertex.clone(),
+                        message,
+                    }
+                })?;
+                operations.insert(vertex.clone(), operation);
+            }
+            Ok((graph, operations))
+        }
+        GraphFormat::Json => {
+            let document: Document =
+                serde_json::from_str(input).map_err(CombinedError::Json)?;
+            let mut graph = Graph::default();
+            for node in document.nodes {
+                if let Some(operation) = node.op {
+                    operations.insert(node.name.clone(), operation);
+                }
+                graph.vertices.push(node.name);
+            }
+            if let Some(vertex) = graph.duplicate_vertex() {
+                return Err(CombinedError::DuplicateVertex(vertex.clone()));
+            }
+            graph.arcs = document.edges;
+            Ok((graph, operations))
+        }
+        format => Err(CombinedError::UnsupportedFormat(format)),
+    }
+}
+
+/// Записывает граф с операциями одним документом в XML или JSON, другие
+/// форматы операции хранить не могут. Операции вершин, которых нет в
+/// графе, не записываются.
+pub fn write_combined<W: Write>(
+    output: W,
+    g: &Graph,
+    operations: &HashMap<String, Operation>,
+    format: GraphFormat,
+) -> Result<(), CombinedError> {
+    match format {
+        GraphFormat::Xml => {
+            let labels: HashMap<String, String> = operations
+                .iter()
+                .map(|(name, op)| (name.clone(), op.to_string()))
+                .collect();
+            let options = XmlOptions::default();
+            return g
+                .write_xml_labeled(
+                    output,
+                    &options,
+                    OPERATION_ATTRIBUTE,
+                    &labels,
+                )
+                .map_err(CombinedError::Io);
+        }
+        GraphFormat::Json => {}
+        format => return Err(CombinedError::UnsupportedFormat(format)),
+    }
+    let document = Document {
+        nodes: g
+            .vertices
+            .iter()
+            .map(|name| Node {
+                name: name.clone(),
+                op: operations.get(name).cloned(),
+            })
+            .collect(),
+        edges: g.arcs.clone(),
+    };
+    serde_json::to_writer_pretty(output, &document).map_err(CombinedError::Json)
+}
@@ -0,0 +1,263 @@
+use crate::number::{Failure, Number};
+use crate::operation::{Arity, Operation};
+use crate::variables::Variables;
+use nngraph::{
+    find_roots, quote_name, topological_order, Cycle, Graph, Node,
+    UndeclaredVertices,
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[derive(Debug)]
+pub enum EvalError {
+    Json(serde_json::Error),
+    InvalidOperation {
+        vertex: String,
+        error: serde_json::Error,
+    },
+    Undeclared(UndeclaredVertices),
+    MissingOperation(String),
+    UnknownVertex(String),
+    Cycle(Cycle),
+    NoRoot,
+    MultipleRoots(Vec<String>),
+    Arity {
+        vertex: String,
+        operation: String,
+        arity: Arity,
+        parents: usize,
+    },
+    UnboundVariable {
+        vertex: String,
+        variable: String,
+    },
+    Unsupported {
+        vertex: String,
+        operation: String,
+        number: &'static str,
+    },
+    Undefined(String),
+    NoDerivative(String),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvalError::Json(err) => {
+                write!(f, "неверный формат файла с операциями: {err}")
+            }
+            EvalError::InvalidOperation { vertex, error } => {
+                write!(f, "вершина {}: {error}", quote_name(vertex))
+            }
+            EvalError::Undeclared(err) => write!(f, "{err}"),
+            EvalError::MissingOperation(vertex) => {
+                write!(
+                    f,
+                    "для вершины {} не задана операция",
+                    quote_name(vertex)
+                )
+            }
+            EvalError::UnknownVertex(vertex) => write!(
+                f,
+                "операция задана для вершины {}, которой нет в графе",
+                quote_name(vertex)
+            ),
+            EvalError::Cycle(cycle) => write!(f, "в графе есть цикл {cycle}"),
+            EvalError::NoRoot => write!(f, "в графе нет корня"),
+            EvalError::MultipleRoots(roots) => {
+                let roots: Vec<_> =
+                    roots.iter().map(|r| quote_name(r)).collect();
+                write!(f, "в графе несколько корней: {}", roots.join(", "))
+            }
+            EvalError::Arity {
+                vertex,
+                operation,
+                arity,
+                parents,
+            } => write!(
+                f,
+                "вершина {}: операции {operation} нужно аргументов: {arity}, \
+                 а заходящих дуг: {parents}",
+                quote_name(vertex)
+            ),
+            EvalError::UnboundVariable { vertex, variable } => write!(
+                f,
+                "вершина {}: не задано значение переменной {variable}",
+                quote_name(vertex)
+            ),
+            EvalError::Unsupported {
+                vertex,
+                operation,
+                number,
+            } => write!(
+                f,
+                "вершина {}: операция {operation} не поддерживается для \
+                 чисел типа {number}",
+                quote_name(vertex)
+            ),
+            EvalError::Undefined(vertex) => {
+                write!(
+                    f,
+                    "значение вершины {} не определено",
+                    quote_name(vertex)
+                )
+            }
+            EvalError::NoDerivative(vertex) => {
+                write!(
+                    f,
+                    "производная вершины {} не определена",
+                    quote_name(vertex)
+                )
+            }
+        }
+    }
+}
+
+/// Значения всех вершин, от которых зависит корень графа. Каждая вершина
+/// вычисляется один раз, в порядке `order`.
+pub struct Evaluation<T = f64> {
+    pub nodes: HashMap<String, Node>,
+    pub root: String,
+    pub order: Vec<String>,
+    pub values: HashMap<String, T>,
+}
+
+impl<T: Number> Evaluation<T> {
+    pub fn value(&self) -> T {
+        self.values[&self.root].clone()
+    }
+
+    pub fn args(&self, name: &str) -> Vec<T> {
+        self.nodes[name]
+            .parents
+            .iter()
+            .map(|parent| self.values[parent].clone())
+            .collect()
+    }
+
+    pub fn trace(
+        &self,
+        operations: &HashMap<String, Operation>,
+    ) -> Vec<String> {
+        self.order
+            .iter()
+            .map(|name| {
+                let value = &self.values[name];
+                let parents = &self.nodes[name].parents;
+                let label = quote_name(name);
+                match &operations[name] {
+                    Operation::Const(_) => format!("{label} = {value}"),
+                    operation if parents.is_empty() => {
+                        format!("{label} = {operation} = {value}")
+                    }
+                    operation => {
+                        let args: Vec<_> =
+                            parents.iter().map(|p| quote_name(p)).collect();
+                        let args = args.join(", ");
+                        format!("{label} = {operation}({args}) = {value}")
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Проверяет, что операции заданы ровно для вершин графа: если граф
+/// ацикличен и у него один корень, все вершины от корня зависят.
+fn check_operations(
+    g: &Graph,
+    operations: &HashMap<String, Operation>,
+) -> Result<(), EvalError> {
+    if let Some(vertex) = g
+        .vertices
+        .iter()
+        .find(|vertex| !operations.contains_key(*vertex))
+    {
+        return Err(EvalError::MissingOperation(vertex.clone()));
+    }
+    let vertices: HashSet<&String> = g.vertices.iter().collect();
+    let mut unknown: Vec<&String> = operations
+        .keys()
+        .filter(|name| !vertices.contains(name))
+        .collect();
+    unknown.sort();
+    match unknown.first() {
+        Some(vertex) => Err(EvalError::UnknownVertex(vertex.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// Проверяет граф и операции и определяет порядок вычисления вершин.
+/// Значения вершин в результате ещё не заполнены.
+pub fn plan<T>(
+    g: &Graph,
+    operations: &HashMap<String, Operation>,
+) -> Result<Evaluation<T>, EvalError> {
+    let nodes = g.nodes().map_err(EvalError::Undeclared)?;
+    if let Some(cycle) = g.cycles().into_iter().next() {
+        return Err(EvalError::Cycle(cycle));
+    }
+    check_operations(g, operations)?;
+    let mut roots = find_roots(&nodes);
+    let root = match roots.len() {
+        0 => return Err(EvalError::NoRoot),
+        1 => roots.pop().unwrap(),
+        _ => return Err(EvalError::MultipleRoots(roots)),
+    };
+    let order = topological_order(&root, &nodes);
+    for name in &order {
+        let operation = &operations[name];
+        let arity = operation.arity();
+        let parents = nodes[name].parents.len();
+        if !arity.accepts(parents) {
+            return Err(EvalError::Arity {
+                vertex: name.clone(),
+                operation: operation.to_string(),
+                arity,
+                parents,
+            });
+        }
+    }
+    Ok(Evaluation {
+        nodes,
+        root,
+        order,
+        values: HashMap::new(),
+    })
+}
+
+pub fn evaluate<T: Number>(
+    g: &Graph,
+    operations: &HashMap<String, Operation>,
+    variables: &Variables,
+) -> Result<Evaluation<T>, EvalError> {
+    let mut evaluation = plan(g, operations)?;
+    for name in &evaluation.order {
+        let operation = &operations[name];
+        let value = match operation {
+            Operation::Const(num) => {
+                T::from_f64(*num).ok_or(Failure::Undefined)
+            }
+            Operation::Var(variable) => match variables.get(variable) {
+                Some(&value) => T::from_f64(value).ok_or(Failure::Undefined),
+                None => {
+                    return Err(EvalError::UnboundVariable {
+                        vertex: name.clone(),
+                        variable: variable.clone(),
+                    })
+                }
+            },
+            operation => T::apply(operation, &evaluation.args(name)),
+        };
+        let value = value.map_err(|failure| match failure {
+            Failure::Unsupported => EvalError::Unsupported {
+                vertex: name.clone(),
+                operation: operation.to_string(),
+                number: T::NAME,
+            },
+            Failure::Undefined => EvalError::Undefined(name.clone()),
+        })?;
+        evaluation.values.insert(name.clone(), value);
+    }
+    Ok(evaluation)
+}
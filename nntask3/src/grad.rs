@@ -0,0 +1,52 @@
+use crate::eval::{evaluate, EvalError, Evaluation};
+use crate::operation::Operation;
+use crate::variables::Variables;
+use nngraph::Graph;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Serialize)]
+pub struct Gradient {
+    pub vertices: BTreeMap<String, f64>,
+    pub variables: BTreeMap<String, f64>,
+}
+
+/// Производные значения корня графа по каждой вершине и по каждой
+/// переменной. Значения вершин вычисляются один раз, производная по
+/// вершине, используемой несколькими дугами, суммируется по всем им.
+/// Если в какой-то вершине производная не определена или бесконечна,
+/// возвращается ошибка, а не NaN в результате.
+pub fn gradient(
+    g: &Graph,
+    operations: &HashMap<String, Operation>,
+    variables: &Variables,
+) -> Result<Gradient, EvalError> {
+    let evaluation: Evaluation = evaluate(g, operations, variables)?;
+    let mut adjoints: HashMap<&String, f64> = HashMap::new();
+    adjoints.insert(&evaluation.root, 1.0);
+    for name in evaluation.order.iter().rev() {
+        let adjoint = adjoints.get(name).copied().unwrap_or(0.0);
+        let args = evaluation.args(name);
+        let partials = operations[name]
+            .partials(&args, evaluation.values[name])
+            .filter(|partials| partials.iter().all(|p| p.is_finite()))
+            .ok_or_else(|| EvalError::NoDerivative(name.clone()))?;
+        for (parent, partial) in
+            evaluation.nodes[name].parents.iter().zip(partials)
+        {
+            *adjoints.entry(parent).or_default() += adjoint * partial;
+        }
+    }
+
+    let mut gradient = Gradient {
+        vertices: BTreeMap::new(),
+        variables: BTreeMap::new(),
+    };
+    for (name, adjoint) in adjoints {
+        if let Operation::Var(variable) = &operations[name] {
+            *gradient.variables.entry(variable.clone()).or_default() += adjoint;
+        }
+        gradient.vertices.insert(name.clone(), adjoint);
+    }
+    Ok(gradient)
+}
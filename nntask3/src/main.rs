@@ -1,276 +1,341 @@
-use clap::Parser;
-use serde::{de::IntoDeserializer, Deserialize, Deserializer};
-use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{Read, Write};
-use xml::reader::{EventReader, XmlEvent};
+mod combined;
+mod eval;
+mod grad;
+mod number;
+mod operation;
+mod render;
+mod simplify;
+mod variables;
 
-#[derive(Debug, Clone)]
-struct Arc {
-    from: String,
-    to: String,
-    order: i32,
-}
-
-#[derive(Debug)]
-struct Graph {
-    vertices: Vec<String>,
-    arcs: Vec<Arc>,
-}
+use clap::{ArgGroup, Parser};
+use combined::write_combined;
+use eval::{evaluate, EvalError, Evaluation};
+use nngraph::{Graph, GraphFormat, LoadError};
+use number::{Interval, Number, NumberType, Rational};
+use operation::Operation;
+use render::{render, Notation};
+use simplify::simplify;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::Write;
+use variables::{parse_binding, Batch, Variables};
 
-#[derive(PartialEq, Eq)]
-enum ParserState {
-    Waiting,
-    Graph,
-    Vertex,
-    Arc,
-    From,
-    To,
-    Order,
+/// Без `--output1` граф только переводится в другой вид (`--merged`,
+/// `--split`) и не вычисляется, поэтому флаги вычисления его требуют.
+#[derive(Parser)]
+#[command(group(
+    ArgGroup::new("evaluation")
+        .multiple(true)
+        .args([
+            "dot", "bindings", "vars", "batch", "grad", "trace", "number",
+            "expr", "simplify", "simplified",
+        ])
+        .requires("output1")
+))]
+struct Config {
+    #[arg(long, value_name = "FILE")]
+    input1: String,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Операции вершин в JSON; без него операции читаются из \
+                --input1 вместе с графом"
+    )]
+    input2: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        required_unless_present_any = ["merged", "split"]
+    )]
+    output1: Option<String>,
+    #[arg(long, help = "Объявить вершины, на которые ссылаются дуги")]
+    auto_declare: bool,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Сохранить граф с операциями в формате DOT"
+    )]
+    dot: Option<String>,
+    #[arg(
+        long = "var",
+        value_name = "NAME=VALUE",
+        value_parser = parse_binding,
+        help = "Значение переменной, можно указать несколько раз"
+    )]
+    bindings: Vec<(String, f64)>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "JSON-файл со значениями переменных"
+    )]
+    vars: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Вычислить функцию во всех точках из CSV-файла"
+    )]
+    batch: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with = "batch",
+        help = "Сохранить в JSON производные корня по вершинам и \
+                переменным, вычисленные в f64"
+    )]
+    grad: Option<String>,
+    #[arg(
+        long,
+        conflicts_with = "batch",
+        help = "Вывести значения всех вершин в порядке вычисления"
+    )]
+    trace: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "f64",
+        help = "Тип чисел, в котором вычисляются значения вершин"
+    )]
+    number: NumberType,
+    #[arg(long, help = "Вывести функцию, вычисляемую графом, в виде формулы")]
+    expr: bool,
+    #[arg(long, requires = "expr", help = "Выводить формулу в нотации LaTeX")]
+    latex: bool,
+    #[arg(long, help = "Упростить граф перед выводом формулы")]
+    simplify: bool,
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "simplified_ops",
+        help = "Сохранить упрощённый граф"
+    )]
+    simplified: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "simplified",
+        help = "Сохранить операции упрощённого графа в JSON"
+    )]
+    simplified_ops: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Сохранить граф вместе с операциями в одном файле"
+    )]
+    merged: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "split_ops",
+        help = "Сохранить граф без операций"
+    )]
+    split: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "split",
+        help = "Сохранить операции вершин в JSON"
+    )]
+    split_ops: Option<String>,
 }
 
-fn parse_input_graph(input_file: &File) -> Option<Graph> {
-    let mut vertices = Vec::new();
-    let mut arcs = Vec::new();
-    let mut arc = Arc {
-        from: String::new(),
-        to: String::new(),
-        order: 0,
-    };
-    let mut state = ParserState::Waiting;
-    let parser = EventReader::new(input_file);
-
-    for e in parser {
-        match e {
-            Ok(XmlEvent::StartElement { name, .. }) => {
-                state = match (name.local_name.as_str(), state) {
-                    ("graph", ParserState::Waiting) => ParserState::Graph,
-                    ("vertex", ParserState::Graph) => ParserState::Vertex,
-                    ("arc", ParserState::Graph) => ParserState::Arc,
-                    ("from", ParserState::Arc) => ParserState::From,
-                    ("to", ParserState::Arc) => ParserState::To,
-                    ("order", ParserState::Arc) => ParserState::Order,
-                    _ => return None,
-                };
+fn read_variables(config: &Config) -> Option<Variables> {
+    let mut variables = Variables::new();
+    if let Some(path) = &config.vars {
+        let input = match fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(_) => {
+                println!("Не удалось прочитать значения переменных из файла");
+                return None;
             }
-            Ok(XmlEvent::EndElement { name, .. }) => {
-                state = match (name.local_name.as_str(), state) {
-                    ("graph", ParserState::Graph) => ParserState::Waiting,
-                    ("vertex", ParserState::Vertex) => ParserState::Graph,
-                    ("arc", ParserState::Arc) => {
-                        arcs.push(arc.clone());
-                        ParserState::Graph
-                    }
-                    ("from", ParserState::From) => ParserState::Arc,
-                    ("to", ParserState::To) => ParserState::Arc,
-                    ("order", ParserState::Order) => ParserState::Arc,
-                    _ => return None,
-                };
+        };
+        match serde_json::from_str::<Variables>(&input) {
+            Ok(values) => variables.extend(values),
+            Err(err) => {
+                println!("Неверный формат файла с переменными: {err}");
+                return None;
             }
-            Ok(XmlEvent::Characters(text)) => match state {
-                ParserState::Vertex => vertices.push(text),
-                ParserState::From => arc.from = text,
-                ParserState::To => arc.to = text,
-                ParserState::Order => arc.order = text.parse::<i32>().unwrap(),
-                _ => return None,
-            },
-            Err(err) => println!("{err}"),
-            _ => {}
         }
     }
-
-    Some(Graph { vertices, arcs })
+    variables.extend(config.bindings.iter().cloned());
+    Some(variables)
 }
 
-#[derive(Debug, Clone, Deserialize)]
-#[serde(remote = "Operation")]
-enum Operation {
-    #[serde(rename = "exp")]
-    Exp,
-    #[serde(rename = "+")]
-    Plus,
-    #[serde(rename = "*")]
-    Mult,
-    Const(f64),
-}
-
-impl<'de> Deserialize<'de> for Operation {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        #[derive(Deserialize)]
-        #[serde(untagged)]
-        enum Possible {
-            A(String),
-            B(f64),
-        }
-        match Possible::deserialize(deserializer).unwrap() {
-            Possible::A(val) => Operation::deserialize(val.into_deserializer()),
-            Possible::B(val) => Ok(Operation::Const(val)),
+fn read_batch(path: &str) -> Option<Batch> {
+    let input = match fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(_) => {
+            println!("Не удалось прочитать точки из указанного файла");
+            return None;
         }
-    }
-}
-
-#[derive(Debug)]
-struct Node {
-    operation: Operation,
-    children: Vec<String>,
-    parents: Vec<String>,
-}
-
-impl Node {
-    fn new(operation: Operation) -> Node {
-        Node {
-            operation,
-            children: Vec::new(),
-            parents: Vec::new(),
+    };
+    match Batch::read(&input) {
+        Ok(batch) => Some(batch),
+        Err(err) => {
+            println!("Некорректный ввод: {err}");
+            None
         }
     }
 }
 
-fn evaluate_node(node: &Node, nodes: &HashMap<String, Node>) -> Option<f64> {
-    let parents = node
-        .parents
-        .iter()
-        .map(|c| evaluate_node(&nodes[c], nodes))
-        .collect::<Vec<Option<f64>>>();
-    match node.operation {
-        Operation::Const(num) => Some(num),
-        Operation::Exp => {
-            if parents.len() != 1 {
-                None
-            } else {
-                Some(parents[0]?.exp())
-            }
+fn read_graph(path: &str) -> Option<Graph> {
+    match Graph::load(path) {
+        Ok(g) => Some(g),
+        Err(LoadError::Io(_)) => {
+            println!("Не удалось прочитать граф из указанного файла");
+            None
         }
-        Operation::Plus => {
-            if parents.len() < 2 {
-                return None;
-            }
-            let mut result = 0.0;
-            for i in &parents {
-                match i {
-                    None => return None,
-                    Some(val) => result += val,
-                }
-            }
-            Some(result)
-        }
-        Operation::Mult => {
-            if parents.len() < 2 {
-                return None;
-            }
-            let mut result = 1.0;
-            for i in &parents {
-                match i {
-                    None => return None,
-                    Some(val) => result *= val,
-                }
-            }
-            Some(result)
+        Err(err) => {
+            println!("Некорректный ввод: {err}");
+            None
         }
     }
 }
 
-fn find_root(nodes: &HashMap<String, Node>) -> Option<String> {
-    let mut root_name = String::new();
-    let mut found = false;
-    for (name, node) in nodes.iter() {
-        if node.children.is_empty() {
-            if found {
-                return None;
-            }
-            root_name = name.clone();
-            found = true;
+fn read_combined(path: &str) -> Option<(Graph, HashMap<String, Operation>)> {
+    let input = match fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(_) => {
+            println!("Не удалось прочитать граф из указанного файла");
+            return None;
+        }
+    };
+    match combined::read_combined(&input, GraphFormat::detect(path, &input)) {
+        Ok(combined) => Some(combined),
+        Err(err) => {
+            println!("Некорректный ввод: {err}");
+            None
         }
     }
-    Some(root_name)
 }
 
-fn has_cycle(
-    root: &String,
-    name: &String,
-    visited: &mut HashSet<String>,
-    nodes: &HashMap<String, Node>,
-) -> bool {
-    if visited.contains(name) {
-        return false;
-    }
-    visited.insert(name.clone());
-    for node in &nodes[name].parents {
-        if node == root {
-            return true;
+fn read_operations(path: &str) -> Option<HashMap<String, Operation>> {
+    let input = match fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(_) => {
+            println!("Не удалось прочитать операции из указанного файла");
+            return None;
         }
-        if has_cycle(root, node, visited, nodes) {
-            return true;
+    };
+    match operation::read_operations(&input) {
+        Ok(operations) => Some(operations),
+        Err(err) => {
+            println!("Некорректный ввод: {err}");
+            None
         }
     }
-    false
 }
 
-fn evaluate_expr(
+/// Записывает граф в формате по расширению `path` и операции в JSON.
+fn write_split(
     g: &Graph,
     operations: &HashMap<String, Operation>,
-) -> Option<f64> {
-    let mut nodes = HashMap::new();
-    for vert in &g.vertices {
-        nodes.insert(vert.clone(), Node::new(operations[vert].clone()));
+    path: &str,
+    ops_path: &str,
+) {
+    let format = GraphFormat::from_path(path).unwrap_or(GraphFormat::Xml);
+    let written = File::create(path).and_then(|file| g.write(file, format));
+    if written.is_err() {
+        println!("Не удалось записать граф в файл {path}");
+        return;
     }
-    for arc in &g.arcs {
-        nodes
-            .get_mut(&arc.from)
-            .unwrap()
-            .children
-            .push(arc.to.clone());
-        nodes
-            .get_mut(&arc.to)
-            .unwrap()
-            .parents
-            .push(arc.from.clone());
+    let operations: BTreeMap<_, _> = operations.iter().collect();
+    let written = File::create(ops_path)
+        .map_err(serde_json::Error::io)
+        .and_then(|file| serde_json::to_writer_pretty(file, &operations));
+    if written.is_err() {
+        println!("Не удалось записать операции в файл {ops_path}");
     }
+}
 
-    let mut visited = HashSet::new();
-    for vert in &g.vertices {
-        if has_cycle(vert, vert, &mut visited, &nodes) {
-            return None;
-        }
-        visited.clear();
+/// Поле CSV с запятой (например, интервал) берётся в кавычки.
+fn csv_field(value: String) -> String {
+    if value.contains(',') {
+        format!("\"{value}\"")
+    } else {
+        value
     }
-
-    let root_name = find_root(&nodes)?;
-    let root_node = &nodes[&root_name];
-
-    evaluate_node(root_node, &nodes)
 }
 
-#[derive(Parser)]
-struct Config {
-    #[arg(long, value_name = "FILE")]
-    input1: String,
-    #[arg(long, value_name = "FILE")]
-    input2: String,
-    #[arg(long, value_name = "FILE")]
-    output1: String,
+/// Значение корня или, с `--batch`, CSV со значениями во всех точках.
+/// `None` означает, что ошибка уже выведена.
+fn compute<T: Number>(
+    config: &Config,
+    g: &Graph,
+    operations: &HashMap<String, Operation>,
+    variables: &Variables,
+) -> Result<Option<String>, EvalError> {
+    let Some(path) = &config.batch else {
+        let evaluation: Evaluation<T> = evaluate(g, operations, variables)?;
+        if config.trace {
+            for line in evaluation.trace(operations) {
+                println!("{line}");
+            }
+        }
+        return Ok(Some(evaluation.value().to_string()));
+    };
+    let Some(batch) = read_batch(path) else {
+        return Ok(None);
+    };
+    let mut lines = vec![batch.names.join(",") + ",result"];
+    for row in &batch.rows {
+        let point = batch.point(row, variables);
+        let evaluation: Evaluation<T> = evaluate(g, operations, &point)?;
+        let mut line: Vec<String> =
+            row.iter().map(|value| value.to_string()).collect();
+        line.push(csv_field(evaluation.value().to_string()));
+        lines.push(line.join(","));
+    }
+    Ok(Some(lines.join("\n")))
 }
 
 fn main() {
     let config = Config::parse();
-    let input = match File::open(config.input1) {
-        Ok(s) => s,
-        Err(_) => {
-            println!("Не удалось прочитать граф из указанного файла");
+    if config.grad.is_some() && config.number != NumberType::F64 {
+        println!("Производные с --grad вычисляются только в f64");
+        return;
+    }
+    let loaded = match &config.input2 {
+        Some(ops_path) => read_graph(&config.input1)
+            .and_then(|g| Some((g, read_operations(ops_path)?))),
+        None => read_combined(&config.input1),
+    };
+    let Some((mut g, operations)) = loaded else {
+        return;
+    };
+    if let Err(err) = g.check_references(config.auto_declare) {
+        println!("Некорректный ввод: {err}");
+        return;
+    }
+    if let Some(path) = &config.merged {
+        let format = GraphFormat::from_path(path).unwrap_or(GraphFormat::Xml);
+        let mut document = Vec::new();
+        if let Err(err) = write_combined(&mut document, &g, &operations, format)
+        {
+            println!(
+                "Не удалось записать граф с операциями в файл {path}: {err}"
+            );
             return;
         }
-    };
-    let g = match parse_input_graph(&input) {
-        Some(g) => g,
-        None => {
-            println!("Некорректный ввод");
+        if fs::write(path, document).is_err() {
+            println!("Не удалось записать граф с операциями в файл {path}");
             return;
         }
+    }
+    if let (Some(path), Some(ops_path)) = (&config.split, &config.split_ops) {
+        write_split(&g, &operations, path, ops_path);
+    }
+    let Some(output1) = &config.output1 else {
+        return;
     };
-    let mut output = match File::create(config.output1) {
+    let cycles = g.cycles();
+    if !cycles.is_empty() {
+        println!("Некорректный ввод - в графе есть циклы:");
+        for cycle in cycles {
+            println!("  {cycle}");
+        }
+        return;
+    }
+    let mut output = match File::create(output1) {
         Ok(out) => out,
         Err(_) => {
             println!("Не удалось создать файл для вывода");
@@ -278,29 +343,95 @@ fn main() {
         }
     };
 
-    let operations = match File::open(config.input2) {
-        Ok(mut ops_f) => {
-            let mut ser = String::new();
-            ops_f
-                .read_to_string(&mut ser)
-                .expect("Не удалось прочитать из файла с операциями");
-            serde_json::from_str(&ser)
-                .expect("Неверный формат файла с операциями")
-        }
-        Err(_) => {
-            println!("Не удалось прочитать операции из указанного файла");
+    if let Some(path) = &config.dot {
+        let labels: HashMap<String, String> = operations
+            .iter()
+            .map(|(name, op)| (name.clone(), op.to_string()))
+            .collect();
+        let written = File::create(path)
+            .and_then(|mut dot| g.write_dot(&mut dot, &labels));
+        if written.is_err() {
+            println!("Не удалось записать граф в формате DOT");
             return;
         }
+    }
+
+    let simplified = if config.simplify || config.simplified.is_some() {
+        match simplify(&g, &operations) {
+            Ok(simplified) => Some(simplified),
+            Err(err) => {
+                println!("Некорректный ввод: {err}");
+                return;
+            }
+        }
+    } else {
+        None
     };
+    if let (Some(simplified), Some(path), Some(ops_path)) =
+        (&simplified, &config.simplified, &config.simplified_ops)
+    {
+        write_split(&simplified.graph, &simplified.operations, path, ops_path);
+    }
+    if config.expr {
+        let notation = if config.latex {
+            Notation::Latex
+        } else {
+            Notation::Plain
+        };
+        let expression = match (&simplified, config.simplify) {
+            (Some(simplified), true) => {
+                render(&simplified.graph, &simplified.operations, notation)
+            }
+            _ => render(&g, &operations, notation),
+        };
+        match expression {
+            Ok(expression) => println!("{expression}"),
+            Err(err) => {
+                println!("Некорректный ввод: {err}");
+                return;
+            }
+        }
+    }
 
-    let call_string = match evaluate_expr(&g, &operations) {
-        Some(s) => s,
-        None => {
-            println!("Некорректный ввод - в графе есть циклы");
+    let variables = match read_variables(&config) {
+        Some(variables) => variables,
+        None => return,
+    };
+    let result = match config.number {
+        NumberType::F32 => compute::<f32>(&config, &g, &operations, &variables),
+        NumberType::F64 => compute::<f64>(&config, &g, &operations, &variables),
+        NumberType::Rational => {
+            compute::<Rational>(&config, &g, &operations, &variables)
+        }
+        NumberType::Interval => {
+            compute::<Interval>(&config, &g, &operations, &variables)
+        }
+    };
+    let result = match result {
+        Ok(Some(result)) => result,
+        Ok(None) => return,
+        Err(err) => {
+            println!("Некорректный ввод: {err}");
             return;
         }
     };
     output
-        .write_all(call_string.to_string().as_bytes())
+        .write_all(result.as_bytes())
         .expect("Не удалось записать вывод в файл");
+
+    if let Some(path) = &config.grad {
+        let gradient = match grad::gradient(&g, &operations, &variables) {
+            Ok(gradient) => gradient,
+            Err(err) => {
+                println!("Некорректный ввод: {err}");
+                return;
+            }
+        };
+        let written = File::create(path)
+            .map_err(serde_json::Error::io)
+            .and_then(|file| serde_json::to_writer_pretty(file, &gradient));
+        if written.is_err() {
+            println!("Не удалось записать производные в файл");
+        }
+    }
 }
@@ -0,0 +1,353 @@
+use crate::operation::Operation;
+use clap::ValueEnum;
+use num_rational::BigRational;
+use num_traits::{Float, Signed, Zero};
+use std::f64::consts::{FRAC_PI_2, PI};
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum NumberType {
+    F32,
+    F64,
+    /// Точные дроби, только для операций без округления
+    Rational,
+    /// Интервалы, гарантированно содержащие точный результат
+    Interval,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Failure {
+    /// Операцию нельзя выполнить в этом типе чисел без потери точности.
+    Unsupported,
+    /// Значение не определено: NaN, деление на ноль, логарифм
+    /// отрицательного числа.
+    Undefined,
+}
+
+/// Тип, в котором вычисляются значения вершин. Константы и значения
+/// переменных задаются как `f64` и переводятся в этот тип через `from_f64`.
+pub trait Number: Clone + fmt::Display {
+    const NAME: &'static str;
+
+    fn from_f64(value: f64) -> Option<Self>;
+
+    /// Операция с аргументами. Значения `Const` и `Var` задаются через
+    /// `from_f64`, здесь для них `Failure::Unsupported`.
+    fn apply(operation: &Operation, args: &[Self]) -> Result<Self, Failure>;
+}
+
+fn float<T: Float>(operation: &Operation, args: &[T]) -> Result<T, Failure> {
+    let Some(value) = operation.apply(args) else {
+        return Err(Failure::Unsupported);
+    };
+    if value.is_nan() {
+        return Err(Failure::Undefined);
+    }
+    Ok(value)
+}
+
+impl Number for f32 {
+    const NAME: &'static str = "f32";
+
+    fn from_f64(value: f64) -> Option<f32> {
+        Some(value as f32)
+    }
+
+    fn apply(operation: &Operation, args: &[f32]) -> Result<f32, Failure> {
+        float(operation, args)
+    }
+}
+
+impl Number for f64 {
+    const NAME: &'static str = "f64";
+
+    fn from_f64(value: f64) -> Option<f64> {
+        Some(value)
+    }
+
+    fn apply(operation: &Operation, args: &[f64]) -> Result<f64, Failure> {
+        float(operation, args)
+    }
+}
+
+/// Каждое конечное `f64` переводится в дробь точно, поэтому результат
+/// отличается от `f64` только ошибками округления в самих операциях.
+#[derive(Clone)]
+pub struct Rational(BigRational);
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Number for Rational {
+    const NAME: &'static str = "rational";
+
+    fn from_f64(value: f64) -> Option<Rational> {
+        BigRational::from_float(value).map(Rational)
+    }
+
+    fn apply(
+        operation: &Operation,
+        args: &[Rational],
+    ) -> Result<Rational, Failure> {
+        let values = args.iter().map(|arg| &arg.0);
+        let value = match operation {
+            Operation::Plus => values.sum(),
+            Operation::Mult => values.product(),
+            Operation::Minus => &args[0].0 - &args[1].0,
+            Operation::Div if args[1].0.is_zero() => {
+                return Err(Failure::Undefined)
+            }
+            Operation::Div => &args[0].0 / &args[1].0,
+            Operation::Neg => -&args[0].0,
+            Operation::Abs => args[0].0.abs(),
+            Operation::Relu if args[0].0.is_negative() => BigRational::zero(),
+            Operation::Relu => args[0].0.clone(),
+            Operation::Max => values.max().unwrap().clone(),
+            Operation::Min => values.min().unwrap().clone(),
+            Operation::Pow => {
+                let exponent = &args[1].0;
+                if !exponent.is_integer() {
+                    return Err(Failure::Unsupported);
+                }
+                let Ok(exponent) = i32::try_from(exponent.to_integer()) else {
+                    return Err(Failure::Unsupported);
+                };
+                if exponent < 0 && args[0].0.is_zero() {
+                    return Err(Failure::Undefined);
+                }
+                num_traits::pow::Pow::pow(&args[0].0, exponent)
+            }
+            _ => return Err(Failure::Unsupported),
+        };
+        Ok(Rational(value))
+    }
+}
+
+/// Отрезок `[lo, hi]`, содержащий точное значение. Концы результата
+/// сдвигаются наружу на единицу последнего разряда: считается, что
+/// арифметика и библиотечные функции ошибаются не больше чем на неё.
+#[derive(Clone, Copy)]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}, {}]", self.lo, self.hi)
+    }
+}
+
+/// Переполнение в бесконечность не должно давать бесконечную нижнюю
+/// границу для конечного значения, и наоборот.
+fn down(x: f64) -> f64 {
+    if x == f64::INFINITY {
+        f64::MAX
+    } else {
+        x.next_down()
+    }
+}
+
+fn up(x: f64) -> f64 {
+    if x == f64::NEG_INFINITY {
+        f64::MIN
+    } else {
+        x.next_up()
+    }
+}
+
+impl Interval {
+    fn point(x: f64) -> Interval {
+        Interval { lo: x, hi: x }
+    }
+
+    /// Наименьший отрезок, содержащий приближённые значения `candidates`,
+    /// с запасом на округление.
+    fn hull(candidates: &[f64]) -> Result<Interval, Failure> {
+        if candidates.iter().any(|x| x.is_nan()) {
+            return Err(Failure::Undefined);
+        }
+        let lo = candidates.iter().copied().fold(f64::INFINITY, f64::min);
+        let hi = candidates.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        Ok(Interval {
+            lo: down(lo),
+            hi: up(hi),
+        })
+    }
+
+    fn monotone(self, f: fn(f64) -> f64) -> Result<Interval, Failure> {
+        Interval::hull(&[f(self.lo), f(self.hi)])
+    }
+
+    fn contains(self, x: f64) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+
+    fn add(self, other: Interval) -> Result<Interval, Failure> {
+        let lo = Interval::hull(&[self.lo + other.lo])?.lo;
+        let hi = Interval::hull(&[self.hi + other.hi])?.hi;
+        Ok(Interval { lo, hi })
+    }
+
+    fn mul(self, other: Interval) -> Result<Interval, Failure> {
+        let products = [
+            self.lo * other.lo,
+            self.lo * other.hi,
+            self.hi * other.lo,
+            self.hi * other.hi,
+        ];
+        Interval::hull(&products)
+    }
+
+    fn neg(self) -> Interval {
+        Interval {
+            lo: -self.hi,
+            hi: -self.lo,
+        }
+    }
+
+    fn div(self, other: Interval) -> Result<Interval, Failure> {
+        if other.contains(0.0) {
+            return Err(Failure::Undefined);
+        }
+        self.mul(Interval::hull(&[1.0 / other.lo, 1.0 / other.hi])?)
+    }
+
+    /// Синус с учётом максимумов в `π/2 + 2πk` и минимумов в `-π/2 + 2πk`
+    /// внутри отрезка. Отрезок сначала расширяется, чтобы ошибка в
+    /// значении π не привела к пропуску экстремума.
+    fn sin(self) -> Result<Interval, Failure> {
+        let mut result = self.monotone(f64::sin)?;
+        let wide = Interval::hull(&[self.lo, self.hi])?;
+        let period = 2.0 * PI;
+        let has = |offset: f64| {
+            let k = ((wide.lo - offset) / period).ceil();
+            offset + k * period <= wide.hi
+        };
+        if wide.hi - wide.lo >= period || has(FRAC_PI_2) {
+            result.hi = 1.0;
+        }
+        if wide.hi - wide.lo >= period || has(-FRAC_PI_2) {
+            result.lo = -1.0;
+        }
+        result.lo = result.lo.max(-1.0);
+        result.hi = result.hi.min(1.0);
+        Ok(result)
+    }
+
+    fn pow(self, exponent: Interval) -> Result<Interval, Failure> {
+        let corners = [
+            self.lo.powf(exponent.lo),
+            self.lo.powf(exponent.hi),
+            self.hi.powf(exponent.lo),
+            self.hi.powf(exponent.hi),
+        ];
+        if self.lo >= 0.0 {
+            return Interval::hull(&corners);
+        }
+        let n = exponent.lo;
+        if exponent.hi != n || n.fract() != 0.0 {
+            return Err(Failure::Undefined);
+        }
+        if n < 0.0 && self.contains(0.0) {
+            return Err(Failure::Undefined);
+        }
+        if n % 2.0 == 0.0 && self.contains(0.0) {
+            return Interval::hull(&[corners[0], corners[3], 0.0.powf(n)]);
+        }
+        Interval::hull(&corners)
+    }
+}
+
+impl Number for Interval {
+    const NAME: &'static str = "interval";
+
+    fn from_f64(value: f64) -> Option<Interval> {
+        if value.is_nan() {
+            return None;
+        }
+        Some(Interval::point(value))
+    }
+
+    fn apply(
+        operation: &Operation,
+        args: &[Interval],
+    ) -> Result<Interval, Failure> {
+        // Без аргументов здесь могут быть только `Const` и `Var`.
+        let Some(&x) = args.first() else {
+            return Err(Failure::Unsupported);
+        };
+        match operation {
+            Operation::Plus => {
+                args[1..].iter().try_fold(x, |sum, &arg| sum.add(arg))
+            }
+            Operation::Mult => args[1..]
+                .iter()
+                .try_fold(x, |product, &arg| product.mul(arg)),
+            Operation::Minus => x.add(args[1].neg()),
+            Operation::Div => x.div(args[1]),
+            Operation::Neg => Ok(x.neg()),
+            Operation::Pow => x.pow(args[1]),
+            Operation::Exp => x.monotone(f64::exp),
+            Operation::Log if x.lo < 0.0 => Err(Failure::Undefined),
+            Operation::Log => x.monotone(f64::ln),
+            Operation::Sin => x.sin(),
+            Operation::Cos => x.add(Interval::point(FRAC_PI_2))?.sin(),
+            Operation::Tanh => x.monotone(f64::tanh),
+            Operation::Sigmoid => {
+                x.monotone(|x| 1.0 / (1.0 + (-x).exp())).map(|result| {
+                    Interval {
+                        lo: result.lo.max(0.0),
+                        hi: result.hi.min(1.0),
+                    }
+                })
+            }
+            Operation::Relu => Ok(Interval {
+                lo: x.lo.max(0.0),
+                hi: x.hi.max(0.0),
+            }),
+            Operation::Abs if x.lo >= 0.0 => Ok(x),
+            Operation::Abs if x.hi <= 0.0 => Ok(x.neg()),
+            Operation::Abs => Ok(Interval {
+                lo: 0.0,
+                hi: x.hi.max(-x.lo),
+            }),
+            Operation::Max => Ok(args.iter().fold(x, |a, b| Interval {
+                lo: a.lo.max(b.lo),
+                hi: a.hi.max(b.hi),
+            })),
+            Operation::Min => Ok(args.iter().fold(x, |a, b| Interval {
+                lo: a.lo.min(b.lo),
+                hi: a.hi.min(b.hi),
+            })),
+            Operation::Const(_) | Operation::Var(_) => {
+                Err(Failure::Unsupported)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unsupported<N: Number>() {
+        for operation in [Operation::Const(1.0), Operation::Var("x".into())] {
+            assert_eq!(
+                N::apply(&operation, &[]).err(),
+                Some(Failure::Unsupported)
+            );
+        }
+    }
+
+    #[test]
+    fn const_and_var_are_unsupported() {
+        unsupported::<f32>();
+        unsupported::<f64>();
+        unsupported::<Rational>();
+        unsupported::<Interval>();
+    }
+}
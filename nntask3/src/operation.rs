@@ -0,0 +1,320 @@
+use crate::eval::EvalError;
+use num_traits::Float;
+use serde::de::{Error, IntoDeserializer};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(remote = "Operation")]
+pub enum Operation {
+    #[serde(rename = "exp")]
+    Exp,
+    #[serde(rename = "+")]
+    Plus,
+    #[serde(rename = "*")]
+    Mult,
+    #[serde(rename = "-")]
+    Minus,
+    #[serde(rename = "/")]
+    Div,
+    #[serde(rename = "pow")]
+    Pow,
+    #[serde(rename = "log")]
+    Log,
+    #[serde(rename = "sin")]
+    Sin,
+    #[serde(rename = "cos")]
+    Cos,
+    #[serde(rename = "tanh")]
+    Tanh,
+    #[serde(rename = "sigmoid")]
+    Sigmoid,
+    #[serde(rename = "relu")]
+    Relu,
+    #[serde(rename = "max")]
+    Max,
+    #[serde(rename = "min")]
+    Min,
+    #[serde(rename = "abs")]
+    Abs,
+    #[serde(rename = "neg")]
+    Neg,
+    Const(f64),
+    #[serde(skip)]
+    Var(String),
+}
+
+impl<'de> Deserialize<'de> for Operation {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Variable {
+            var: String,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Possible {
+            A(String),
+            B(f64),
+            C(Variable),
+        }
+        let possible = Possible::deserialize(deserializer).map_err(|_| {
+            D::Error::custom(
+                "ожидалось имя операции, число или объект вида {\"var\": имя}",
+            )
+        })?;
+        match possible {
+            Possible::A(val) => {
+                Operation::deserialize(val.as_str().into_deserializer())
+                    .map_err(|_: D::Error| {
+                        D::Error::custom(format!(
+                            "неизвестная операция '{val}'"
+                        ))
+                    })
+            }
+            Possible::B(val) => Ok(Operation::Const(val)),
+            Possible::C(val) => Ok(Operation::Var(val.var)),
+        }
+    }
+}
+
+/// Операция записывается так же, как читается: числом, объектом с
+/// переменной или именем.
+impl Serialize for Operation {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Operation::Const(num) => serializer.serialize_f64(*num),
+            Operation::Var(name) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("var", name)?;
+                map.end()
+            }
+            operation => serializer.collect_str(operation),
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operation::Exp => write!(f, "exp"),
+            Operation::Plus => write!(f, "+"),
+            Operation::Mult => write!(f, "*"),
+            Operation::Minus => write!(f, "-"),
+            Operation::Div => write!(f, "/"),
+            Operation::Pow => write!(f, "pow"),
+            Operation::Log => write!(f, "log"),
+            Operation::Sin => write!(f, "sin"),
+            Operation::Cos => write!(f, "cos"),
+            Operation::Tanh => write!(f, "tanh"),
+            Operation::Sigmoid => write!(f, "sigmoid"),
+            Operation::Relu => write!(f, "relu"),
+            Operation::Max => write!(f, "max"),
+            Operation::Min => write!(f, "min"),
+            Operation::Abs => write!(f, "abs"),
+            Operation::Neg => write!(f, "neg"),
+            Operation::Const(num) => write!(f, "{num}"),
+            Operation::Var(name) => write!(f, "var {name}"),
+        }
+    }
+}
+
+/// Разбирает запись операции в том виде, в каком её выводит `Display`:
+/// имя операции, число или `var ИМЯ`.
+impl FromStr for Operation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(name) = s.strip_prefix("var ") {
+            return Ok(Operation::Var(name.to_string()));
+        }
+        if let Ok(num) = s.trim().parse::<f64>() {
+            return Ok(Operation::Const(num));
+        }
+        let name: serde::de::value::StrDeserializer<serde::de::value::Error> =
+            s.trim().into_deserializer();
+        Operation::deserialize(name)
+            .map_err(|_| format!("неизвестная операция '{s}'"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    pub fn accepts(self, count: usize) -> bool {
+        match self {
+            Arity::Exactly(n) => count == n,
+            Arity::AtLeast(n) => count >= n,
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Arity::Exactly(n) => write!(f, "{n}"),
+            Arity::AtLeast(n) => write!(f, "не менее {n}"),
+        }
+    }
+}
+
+/// Операции вершин из JSON-объекта, ошибка в операции сообщается вместе с
+/// именем вершины.
+pub fn read_operations(
+    input: &str,
+) -> Result<HashMap<String, Operation>, EvalError> {
+    let entries: Map<String, Value> =
+        serde_json::from_str(input).map_err(EvalError::Json)?;
+    let mut operations = HashMap::new();
+    for (vertex, value) in entries {
+        match <Operation as Deserialize>::deserialize(value) {
+            Ok(operation) => {
+                operations.insert(vertex, operation);
+            }
+            Err(error) => {
+                return Err(EvalError::InvalidOperation { vertex, error })
+            }
+        }
+    }
+    Ok(operations)
+}
+
+impl Operation {
+    pub fn arity(&self) -> Arity {
+        match self {
+            Operation::Const(_) | Operation::Var(_) => Arity::Exactly(0),
+            Operation::Exp
+            | Operation::Log
+            | Operation::Sin
+            | Operation::Cos
+            | Operation::Tanh
+            | Operation::Sigmoid
+            | Operation::Relu
+            | Operation::Abs
+            | Operation::Neg => Arity::Exactly(1),
+            Operation::Minus | Operation::Div | Operation::Pow => {
+                Arity::Exactly(2)
+            }
+            Operation::Plus
+            | Operation::Mult
+            | Operation::Max
+            | Operation::Min => Arity::AtLeast(2),
+        }
+    }
+
+    /// Аргументы передаются в порядке дуг, заходящих в вершину, их
+    /// количество должно быть уже проверено по `arity`. У `Const` и `Var`
+    /// нет аргументов, их значения берутся не отсюда, для них `None`.
+    pub fn apply<T: Float>(&self, args: &[T]) -> Option<T> {
+        let value = match self {
+            Operation::Const(_) | Operation::Var(_) => return None,
+            Operation::Exp => args[0].exp(),
+            Operation::Plus => args.iter().fold(T::zero(), |a, &b| a + b),
+            Operation::Mult => args.iter().fold(T::one(), |a, &b| a * b),
+            Operation::Minus => args[0] - args[1],
+            Operation::Div => args[0] / args[1],
+            Operation::Pow => args[0].powf(args[1]),
+            Operation::Log => args[0].ln(),
+            Operation::Sin => args[0].sin(),
+            Operation::Cos => args[0].cos(),
+            Operation::Tanh => args[0].tanh(),
+            Operation::Sigmoid => T::one() / (T::one() + (-args[0]).exp()),
+            Operation::Relu => args[0].max(T::zero()),
+            Operation::Max => {
+                args.iter().copied().fold(T::neg_infinity(), T::max)
+            }
+            Operation::Min => args.iter().copied().fold(T::infinity(), T::min),
+            Operation::Abs => args[0].abs(),
+            Operation::Neg => -args[0],
+        };
+        Some(value)
+    }
+
+    /// Частные производные значения `value` операции по каждому из
+    /// аргументов. Для `max` и `min` производная равна 1 только по первому
+    /// аргументу, на котором достигается экстремум. `None`, если
+    /// производной нет: степень с отрицательным основанием определена
+    /// только для целых показателей.
+    pub fn partials(&self, args: &[f64], value: f64) -> Option<Vec<f64>> {
+        let extremum = || {
+            let index = args.iter().position(|&arg| arg == value);
+            (0..args.len())
+                .map(|i| if Some(i) == index { 1.0 } else { 0.0 })
+                .collect()
+        };
+        let partials = match self {
+            Operation::Const(_) | Operation::Var(_) => Vec::new(),
+            Operation::Exp => vec![value],
+            Operation::Plus => vec![1.0; args.len()],
+            Operation::Mult => (0..args.len())
+                .map(|i| {
+                    args.iter()
+                        .enumerate()
+                        .filter(|&(j, _)| j != i)
+                        .map(|(_, arg)| arg)
+                        .product()
+                })
+                .collect(),
+            Operation::Minus => vec![1.0, -1.0],
+            Operation::Div => {
+                vec![1.0 / args[1], -args[0] / (args[1] * args[1])]
+            }
+            Operation::Pow if args[0] < 0.0 => return None,
+            Operation::Pow => {
+                // При нулевом основании 0^y = 0 для всех y > 0, поэтому
+                // производная по показателю равна нулю, а не 0 * ln 0.
+                let exponent = if args[0] > 0.0 {
+                    value * args[0].ln()
+                } else {
+                    0.0
+                };
+                vec![args[1] * args[0].powf(args[1] - 1.0), exponent]
+            }
+            Operation::Log => vec![1.0 / args[0]],
+            Operation::Sin => vec![args[0].cos()],
+            Operation::Cos => vec![-args[0].sin()],
+            Operation::Tanh => vec![1.0 - value * value],
+            Operation::Sigmoid => vec![value * (1.0 - value)],
+            Operation::Relu => vec![if args[0] > 0.0 { 1.0 } else { 0.0 }],
+            Operation::Max | Operation::Min => extremum(),
+            Operation::Abs => vec![if args[0] > 0.0 {
+                1.0
+            } else if args[0] < 0.0 {
+                -1.0
+            } else {
+                0.0
+            }],
+            Operation::Neg => vec![-1.0],
+        };
+        Some(partials)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pow_partials() {
+        let partials = Operation::Pow.partials(&[2.0, 3.0], 8.0).unwrap();
+        assert_eq!(partials, vec![12.0, 8.0 * 2f64.ln()]);
+        let partials = Operation::Pow.partials(&[0.0, 2.0], 0.0).unwrap();
+        assert_eq!(partials, vec![0.0, 0.0]);
+        assert_eq!(Operation::Pow.partials(&[-2.0, 2.0], 4.0), None);
+    }
+}
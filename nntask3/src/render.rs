@@ -0,0 +1,233 @@
+use crate::eval::{plan, EvalError, Evaluation};
+use crate::operation::Operation;
+use nngraph::Graph;
+use std::collections::{HashMap, HashSet};
+
+const SUM: u8 = 1;
+const PRODUCT: u8 = 2;
+const NEGATION: u8 = 3;
+const POWER: u8 = 4;
+const ATOM: u8 = 5;
+
+#[derive(Clone, Copy)]
+pub enum Notation {
+    Plain,
+    Latex,
+}
+
+/// Запись подвыражения и приоритет её внешней операции.
+#[derive(Clone)]
+struct Rendered {
+    text: String,
+    precedence: u8,
+}
+
+impl Notation {
+    fn parenthesize(self, text: &str) -> String {
+        match self {
+            Notation::Plain => format!("({text})"),
+            Notation::Latex => format!("\\left({text}\\right)"),
+        }
+    }
+
+    /// Скобки ставятся, если приоритет аргумента ниже `min`. Отрицание
+    /// берётся в скобки везде, кроме начала выражения: `a + (-b)`.
+    fn operand(self, arg: &Rendered, min: u8, first: bool) -> String {
+        if arg.precedence < min || (!first && arg.precedence == NEGATION) {
+            self.parenthesize(&arg.text)
+        } else {
+            arg.text.clone()
+        }
+    }
+
+    fn infix(self, args: &[&Rendered], separator: &str, min: u8) -> String {
+        let operands: Vec<String> = args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| self.operand(arg, min, i == 0))
+            .collect();
+        operands.join(separator)
+    }
+
+    fn function(self, operation: &Operation) -> String {
+        match (self, operation) {
+            (Notation::Plain, operation) => operation.to_string(),
+            (Notation::Latex, Operation::Log) => "\\ln".to_string(),
+            (Notation::Latex, Operation::Sigmoid) => "\\sigma".to_string(),
+            (Notation::Latex, Operation::Relu) => {
+                "\\operatorname{relu}".to_string()
+            }
+            (Notation::Latex, operation) => format!("\\{operation}"),
+        }
+    }
+
+    fn variable(self, name: &str) -> String {
+        match self {
+            Notation::Plain => name.to_string(),
+            Notation::Latex => {
+                let mut escaped = String::new();
+                for c in name.chars() {
+                    match c {
+                        '\\' => escaped.push_str("\\backslash "),
+                        '#' | '$' | '%' | '&' | '_' | '{' | '}' => {
+                            escaped.push('\\');
+                            escaped.push(c);
+                        }
+                        '~' | '^' => escaped.push_str(&format!("\\{c}{{}}")),
+                        c => escaped.push(c),
+                    }
+                }
+                if name.chars().count() == 1 {
+                    escaped
+                } else {
+                    format!("\\mathit{{{escaped}}}")
+                }
+            }
+        }
+    }
+
+    /// Общие подвыражения перечисляются в порядке вычисления, каждое может
+    /// ссылаться на предыдущие.
+    fn bind(self, bindings: Vec<(String, String)>, body: String) -> String {
+        if bindings.is_empty() {
+            return body;
+        }
+        match self {
+            Notation::Plain => {
+                let mut result = String::new();
+                for (name, expression) in bindings {
+                    result.push_str(&format!("let {name} = {expression} in "));
+                }
+                result + &body
+            }
+            Notation::Latex => {
+                let bindings: Vec<String> = bindings
+                    .iter()
+                    .map(|(name, expression)| format!("{name} = {expression}"))
+                    .collect();
+                format!(
+                    "{body}, \\quad \\text{{где }} {}",
+                    bindings.join(", \\; ")
+                )
+            }
+        }
+    }
+
+    fn render(self, operation: &Operation, args: &[&Rendered]) -> Rendered {
+        let (text, precedence) = match (self, operation) {
+            (_, Operation::Const(num)) if num.is_sign_negative() => {
+                (num.to_string(), NEGATION)
+            }
+            (_, Operation::Const(num)) => (num.to_string(), ATOM),
+            (_, Operation::Var(name)) => (self.variable(name), ATOM),
+            (_, Operation::Plus) => (self.infix(args, " + ", SUM), SUM),
+            (Notation::Plain, Operation::Mult) => {
+                (self.infix(args, " * ", PRODUCT), PRODUCT)
+            }
+            (Notation::Latex, Operation::Mult) => {
+                (self.infix(args, " \\cdot ", PRODUCT), PRODUCT)
+            }
+            (_, Operation::Minus) => {
+                let left = self.operand(args[0], SUM, true);
+                let right = self.operand(args[1], PRODUCT, false);
+                (format!("{left} - {right}"), SUM)
+            }
+            (Notation::Plain, Operation::Div) => {
+                let left = self.operand(args[0], PRODUCT, true);
+                let right = self.operand(args[1], POWER, false);
+                (format!("{left} / {right}"), PRODUCT)
+            }
+            (Notation::Latex, Operation::Div) => (
+                format!("\\frac{{{}}}{{{}}}", args[0].text, args[1].text),
+                ATOM,
+            ),
+            (Notation::Plain, Operation::Pow) => {
+                let base = self.operand(args[0], ATOM, true);
+                let exponent = self.operand(args[1], POWER, false);
+                (format!("{base}^{exponent}"), POWER)
+            }
+            (Notation::Latex, Operation::Pow) => {
+                let base = self.operand(args[0], ATOM, true);
+                (format!("{base}^{{{}}}", args[1].text), POWER)
+            }
+            (_, Operation::Neg) => {
+                (format!("-{}", self.operand(args[0], POWER, true)), NEGATION)
+            }
+            (Notation::Latex, Operation::Abs) => {
+                (format!("\\left|{}\\right|", args[0].text), ATOM)
+            }
+            (_, operation) => {
+                let args: Vec<&str> =
+                    args.iter().map(|arg| arg.text.as_str()).collect();
+                let args = args.join(", ");
+                let function = self.function(operation);
+                match self {
+                    Notation::Plain => (format!("{function}({args})"), ATOM),
+                    Notation::Latex => {
+                        (format!("{function}\\left({args}\\right)"), ATOM)
+                    }
+                }
+            }
+        };
+        Rendered { text, precedence }
+    }
+}
+
+/// Формула, вычисляемая графом. Вершина с аргументами, используемая
+/// несколькими дугами, записывается один раз под своим именем: `let c =
+/// exp(x) in c * c`. Если имя совпадает с переменной, к нему добавляется
+/// номер.
+pub fn render(
+    g: &Graph,
+    operations: &HashMap<String, Operation>,
+    notation: Notation,
+) -> Result<String, EvalError> {
+    let evaluation: Evaluation = plan(g, operations)?;
+    let mut uses: HashMap<&String, usize> = HashMap::new();
+    for name in &evaluation.order {
+        for parent in &evaluation.nodes[name].parents {
+            *uses.entry(parent).or_default() += 1;
+        }
+    }
+    let mut taken: HashSet<String> = operations
+        .values()
+        .filter_map(|operation| match operation {
+            Operation::Var(name) => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut bindings = Vec::new();
+    let mut rendered: HashMap<&String, Rendered> = HashMap::new();
+    for name in &evaluation.order {
+        let parents = &evaluation.nodes[name].parents;
+        // Аргумент, используемый один раз, больше не нужен: его запись
+        // переносится в формулу вершины, а не копируется.
+        let args: Vec<Rendered> = parents
+            .iter()
+            .map(|parent| match uses[parent] {
+                1 => rendered.remove(parent).unwrap(),
+                _ => rendered[parent].clone(),
+            })
+            .collect();
+        let args: Vec<&Rendered> = args.iter().collect();
+        let mut result = notation.render(&operations[name], &args);
+        if !parents.is_empty() && uses.get(name).is_some_and(|&n| n > 1) {
+            let binding = (0..)
+                .map(|k| match k {
+                    0 => name.clone(),
+                    k => format!("{name}_{k}"),
+                })
+                .find(|binding| taken.insert(binding.clone()))
+                .unwrap();
+            let binding = notation.variable(&binding);
+            bindings.push((binding.clone(), result.text));
+            result = Rendered {
+                text: binding,
+                precedence: ATOM,
+            };
+        }
+        rendered.insert(name, result);
+    }
+    let body = rendered.remove(&evaluation.root).unwrap().text;
+    Ok(notation.bind(bindings, body))
+}
@@ -0,0 +1,210 @@
+use crate::eval::{plan, EvalError, Evaluation};
+use crate::operation::Operation;
+use nngraph::{Arc, Graph, Node};
+use std::collections::{HashMap, HashSet};
+use std::mem;
+
+pub struct Simplified {
+    pub graph: Graph,
+    pub operations: HashMap<String, Operation>,
+}
+
+/// Строит упрощённый граф вершина за вершиной. Вершина исходного графа
+/// либо переносится под своим именем, либо заменяется уже построенной.
+struct Builder<'a> {
+    nodes: &'a HashMap<String, Node>,
+    names: HashSet<String>,
+    operations: HashMap<String, Operation>,
+    parents: HashMap<String, Vec<String>>,
+    order: Vec<String>,
+}
+
+impl Builder<'_> {
+    fn add(
+        &mut self,
+        name: &str,
+        operation: Operation,
+        parents: Vec<String>,
+    ) -> String {
+        self.operations.insert(name.to_string(), operation);
+        self.parents.insert(name.to_string(), parents);
+        self.order.push(name.to_string());
+        name.to_string()
+    }
+
+    fn fresh(&mut self, base: &str) -> String {
+        (1..)
+            .map(|k| format!("{base}_{k}"))
+            .find(|name| self.names.insert(name.clone()))
+            .unwrap()
+    }
+
+    fn constant(&self, name: &str) -> Option<f64> {
+        match self.operations[name] {
+            Operation::Const(num) => Some(num),
+            _ => None,
+        }
+    }
+
+    /// Аргумент можно раскрыть внутри вершины, если он больше нигде не
+    /// используется: иначе его пришлось бы вычислять дважды.
+    fn flattens_into(&self, arg: &str, operation: &Operation) -> bool {
+        mem::discriminant(&self.operations[arg]) == mem::discriminant(operation)
+            && self.nodes.get(arg).is_some_and(|n| n.children.len() == 1)
+    }
+
+    fn vertex(
+        &mut self,
+        name: &str,
+        operation: Operation,
+        args: Vec<String>,
+    ) -> String {
+        match operation {
+            Operation::Plus | Operation::Mult => {
+                self.associative(name, operation, args)
+            }
+            Operation::Neg
+                if matches!(self.operations[&args[0]], Operation::Neg) =>
+            {
+                self.parents[&args[0]][0].clone()
+            }
+            _ => self.other(name, operation, args),
+        }
+    }
+
+    fn associative(
+        &mut self,
+        name: &str,
+        operation: Operation,
+        args: Vec<String>,
+    ) -> String {
+        let mut flat = Vec::new();
+        for arg in args {
+            if self.flattens_into(&arg, &operation) {
+                flat.extend(self.parents[&arg].iter().cloned());
+            } else {
+                flat.push(arg);
+            }
+        }
+        let constants: Vec<(usize, f64)> = flat
+            .iter()
+            .enumerate()
+            .filter_map(|(i, arg)| Some((i, self.constant(arg)?)))
+            .collect();
+        let values: Vec<f64> = constants.iter().map(|&(_, num)| num).collect();
+        let total = operation.apply(&values).filter(|total| total.is_finite());
+        let Some(total) = total else {
+            return self.add(name, operation, flat);
+        };
+        if constants.len() == flat.len() {
+            return self.add(name, Operation::Const(total), Vec::new());
+        }
+        let identity = operation.apply::<f64>(&[]);
+        let mut args = Vec::new();
+        for (i, arg) in flat.into_iter().enumerate() {
+            match constants.first() {
+                Some(&(first, _)) if first == i && Some(total) != identity => {
+                    if constants.len() == 1 {
+                        args.push(arg);
+                    } else {
+                        let constant = self.fresh(name);
+                        args.push(self.add(
+                            &constant,
+                            Operation::Const(total),
+                            Vec::new(),
+                        ));
+                    }
+                }
+                _ if self.constant(&arg).is_some() => {}
+                _ => args.push(arg),
+            }
+        }
+        if args.len() == 1 {
+            return args.pop().unwrap();
+        }
+        self.add(name, operation, args)
+    }
+
+    fn other(
+        &mut self,
+        name: &str,
+        operation: Operation,
+        args: Vec<String>,
+    ) -> String {
+        let constants: Option<Vec<f64>> =
+            args.iter().map(|arg| self.constant(arg)).collect();
+        if let Some(values) = constants.filter(|_| !args.is_empty()) {
+            let value = operation.apply(&values);
+            if let Some(value) = value.filter(|value| value.is_finite()) {
+                return self.add(name, Operation::Const(value), Vec::new());
+            }
+        }
+        let identity = match operation {
+            Operation::Minus => Some(0.0),
+            Operation::Div | Operation::Pow => Some(1.0),
+            _ => None,
+        };
+        if identity.is_some() && self.constant(&args[1]) == identity {
+            return args[0].clone();
+        }
+        self.add(name, operation, args)
+    }
+
+    /// Оставляет только вершины, от которых зависит `root`, корень
+    /// оказывается первым.
+    fn finish(mut self, root: &str) -> Simplified {
+        let mut reachable = HashSet::new();
+        let mut stack = vec![root];
+        while let Some(name) = stack.pop() {
+            if reachable.insert(name) {
+                stack.extend(self.parents[name].iter().map(String::as_str));
+            }
+        }
+        let mut graph = Graph::default();
+        let mut operations = HashMap::new();
+        for name in self.order.iter().rev() {
+            if !reachable.contains(name.as_str()) {
+                continue;
+            }
+            graph.vertices.push(name.clone());
+            for (i, parent) in self.parents[name].iter().enumerate() {
+                graph.arcs.push(Arc {
+                    from: parent.clone(),
+                    to: name.clone(),
+                    order: i as i32 + 1,
+                });
+            }
+            let operation = self.operations.remove(name).unwrap();
+            operations.insert(name.clone(), operation);
+        }
+        Simplified { graph, operations }
+    }
+}
+
+/// Сворачивает константы, убирает нейтральные аргументы (`x + 0`, `x * 1`,
+/// `x - 0`, `x / 1`, `pow(x, 1)`) и двойное отрицание, раскрывает вложенные
+/// `+` и `*`. Значение корня при любых значениях переменных не меняется.
+pub fn simplify(
+    g: &Graph,
+    operations: &HashMap<String, Operation>,
+) -> Result<Simplified, EvalError> {
+    let evaluation: Evaluation = plan(g, operations)?;
+    let mut builder = Builder {
+        nodes: &evaluation.nodes,
+        names: g.vertices.iter().cloned().collect(),
+        operations: HashMap::new(),
+        parents: HashMap::new(),
+        order: Vec::new(),
+    };
+    let mut aliases: HashMap<&String, String> = HashMap::new();
+    for name in &evaluation.order {
+        let args = evaluation.nodes[name]
+            .parents
+            .iter()
+            .map(|parent| aliases[parent].clone())
+            .collect();
+        let target = builder.vertex(name, operations[name].clone(), args);
+        aliases.insert(name, target);
+    }
+    Ok(builder.finish(&aliases[&evaluation.root]))
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::fmt;
+
+pub type Variables = HashMap<String, f64>;
+
+pub fn parse_binding(s: &str) -> Result<(String, f64), String> {
+    let Some((name, value)) = s.split_once('=') else {
+        return Err(format!("ожидалось ИМЯ=ЗНАЧЕНИЕ, получено '{s}'"));
+    };
+    match value.trim().parse::<f64>() {
+        Ok(value) => Ok((name.trim().to_string(), value)),
+        Err(_) => Err(format!("значение '{value}' не является числом")),
+    }
+}
+
+/// Набор точек из CSV: первая строка содержит имена переменных, каждая
+/// следующая — их значения в одной точке.
+pub struct Batch {
+    pub names: Vec<String>,
+    pub rows: Vec<Vec<f64>>,
+}
+
+pub enum BatchError {
+    Empty,
+    Columns {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    Value {
+        line: usize,
+        value: String,
+    },
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BatchError::Empty => write!(f, "нет строки с именами переменных"),
+            BatchError::Columns {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "строка {line}: ожидалось значений: {expected}, найдено: \
+                 {found}"
+            ),
+            BatchError::Value { line, value } => {
+                write!(f, "строка {line}: '{value}' не является числом")
+            }
+        }
+    }
+}
+
+impl Batch {
+    pub fn read(input: &str) -> Result<Batch, BatchError> {
+        let mut lines = input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        let Some((_, header)) = lines.next() else {
+            return Err(BatchError::Empty);
+        };
+        let names: Vec<String> = header
+            .split(',')
+            .map(|name| name.trim().to_string())
+            .collect();
+        let mut rows = Vec::new();
+        for (index, text) in lines {
+            let line = index + 1;
+            let values: Vec<&str> = text.split(',').collect();
+            if values.len() != names.len() {
+                return Err(BatchError::Columns {
+                    line,
+                    expected: names.len(),
+                    found: values.len(),
+                });
+            }
+            let mut row = Vec::new();
+            for value in values {
+                match value.trim().parse::<f64>() {
+                    Ok(value) => row.push(value),
+                    Err(_) => {
+                        return Err(BatchError::Value {
+                            line,
+                            value: value.trim().to_string(),
+                        })
+                    }
+                }
+            }
+            rows.push(row);
+        }
+        Ok(Batch { names, rows })
+    }
+
+    pub fn point(&self, row: &[f64], defaults: &Variables) -> Variables {
+        let mut point = defaults.clone();
+        point.extend(self.names.iter().cloned().zip(row.iter().copied()));
+        point
+    }
+}
@@ -0,0 +1,160 @@
+//! Запуски nntask3 на файлах из каталога `tests` в корне репозитория с
+//! проверкой вывода по ожидаемым файлам.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests")
+        .join(name)
+}
+
+fn read_fixture(name: &str) -> String {
+    fs::read_to_string(fixture(name)).unwrap()
+}
+
+fn temporary(name: &str) -> PathBuf {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_file(&path);
+    path
+}
+
+/// Запускает nntask3 с `--input1 input --output1 output`, возвращает
+/// стандартный вывод и содержимое `output`.
+fn run(input: &str, output: &str, args: &[&str]) -> (String, String) {
+    let output = temporary(output);
+    let result = Command::new(env!("CARGO_BIN_EXE_nntask3"))
+        .arg("--input1")
+        .arg(fixture(input))
+        .arg("--output1")
+        .arg(&output)
+        .args(args)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(result.stdout).unwrap();
+    (stdout, fs::read_to_string(output).unwrap_or_default())
+}
+
+#[test]
+fn value() {
+    let ops = fixture("t3_ops.json");
+    let args = ["--input2", ops.to_str().unwrap()];
+    let (stdout, output) = run("t1_output.xml", "t3_value.txt", &args);
+    assert_eq!(stdout, "");
+    assert_eq!(output, read_fixture("t3_output.txt"));
+}
+
+#[test]
+fn cycle_message() {
+    let ops = fixture("t3_ops.json");
+    let args = ["--input2", ops.to_str().unwrap()];
+    let (stdout, output) = run("t2_input_cycle.xml", "t3_cycle.txt", &args);
+    assert_eq!(stdout, read_fixture("t2_output_cycle.txt"));
+    assert_eq!(output, "");
+}
+
+#[test]
+fn interval() {
+    let ops = fixture("t3_ops.json");
+    let args = ["--input2", ops.to_str().unwrap(), "--number", "interval"];
+    let (_, output) = run("t1_output.xml", "t3_interval.txt", &args);
+    assert_eq!(output, read_fixture("t3_output_interval.txt"));
+    let value: f64 = read_fixture("t3_output.txt").parse().unwrap();
+    let bounds: Vec<f64> = output
+        .trim_matches(['[', ']'])
+        .split(", ")
+        .map(|bound| bound.parse().unwrap())
+        .collect();
+    assert!(bounds[0] < value && value < bounds[1]);
+}
+
+#[test]
+fn grad_requires_f64() {
+    let ops = fixture("t3_ops.json");
+    let grad = temporary("t3_grad_interval.json");
+    let args = [
+        "--input2",
+        ops.to_str().unwrap(),
+        "--number",
+        "interval",
+        "--grad",
+        grad.to_str().unwrap(),
+    ];
+    let (stdout, output) = run("t1_output.xml", "t3_grad_interval.txt", &args);
+    assert!(stdout.starts_with("Производные с --grad вычисляются только в f64"));
+    assert_eq!(output, "");
+    assert!(!grad.exists());
+}
+
+#[test]
+fn simplify() {
+    let ops = fixture("t3_simplify_ops.json");
+    let simplified = temporary("t3_simplified.xml");
+    let simplified_ops = temporary("t3_simplified_ops.json");
+    let args = [
+        "--input2",
+        ops.to_str().unwrap(),
+        "--expr",
+        "--simplify",
+        "--simplified",
+        simplified.to_str().unwrap(),
+        "--simplified-ops",
+        simplified_ops.to_str().unwrap(),
+        "--var",
+        "x=2",
+    ];
+    let (stdout, output) = run("t1_output.xml", "t3_simplify.txt", &args);
+    assert_eq!(stdout, read_fixture("t3_output_simplified.txt"));
+    assert_eq!(output, "3");
+    assert_eq!(
+        fs::read_to_string(simplified).unwrap(),
+        read_fixture("t3_simplified.xml")
+    );
+    assert_eq!(
+        fs::read_to_string(simplified_ops).unwrap(),
+        read_fixture("t3_simplified_ops.json")
+    );
+}
+
+#[test]
+fn merged_round_trip() {
+    let merged = temporary("t3_merged.xml");
+    let args = ["--merged", merged.to_str().unwrap()];
+    let (_, output) = run("t3_combined.xml", "t3_merged.txt", &args);
+    assert_eq!(output, read_fixture("t3_output.txt"));
+    assert_eq!(
+        fs::read_to_string(merged).unwrap(),
+        read_fixture("t3_combined.xml")
+    );
+    let dot = temporary("t3_merged.dot");
+    let args = ["--merged", dot.to_str().unwrap()];
+    let (stdout, _) = run("t3_combined.xml", "t3_merged_dot.txt", &args);
+    assert!(stdout.starts_with("Не удалось записать граф с операциями"));
+    assert!(!dot.exists());
+}
+
+#[test]
+fn split_without_evaluation() {
+    let graph = temporary("t3_split.xml");
+    let ops = temporary("t3_split_ops.json");
+    let result = Command::new(env!("CARGO_BIN_EXE_nntask3"))
+        .arg("--input1")
+        .arg(fixture("t3_combined_unbound.xml"))
+        .arg("--split")
+        .arg(&graph)
+        .arg("--split-ops")
+        .arg(&ops)
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+    assert_eq!(String::from_utf8(result.stdout).unwrap(), "");
+    assert_eq!(
+        fs::read_to_string(graph).unwrap(),
+        read_fixture("t1_output.xml")
+    );
+    let ops: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(ops).unwrap()).unwrap();
+    assert_eq!(ops["d"]["var"], "x");
+}
@@ -1,9 +1,42 @@
 .\nntask1.exe --input1 tests\t1_input.txt --output1 t1_output.xml
+fc t1_output.xml tests\t1_output.xml
+.\nntask1.exe --input1 tests\t1_input_implicit.txt --output1 t1_output_implicit.xml
+fc t1_output_implicit.xml tests\t1_output_implicit.xml
+.\nntask1.exe --input1 tests\t1_input.txt --output1 t1_output.graphml
 
 .\nntask2.exe --input1 tests\t2_input.xml --output1 t2_output1.xml
-.\nntask2.exe --input1 tests\t2_input_cycle.xml --output1 t2_output2.xml
+.\nntask2.exe --input1 tests\t2_input_cycle.xml --output1 t2_output2.xml > t2_output_cycle.txt
+fc t2_output_cycle.txt tests\t2_output_cycle.txt
+.\nntask2.exe --input1 tests\t2_input.xml --output1 t2_output3.json --format json
+.\nntask2.exe --reverse --input1 tests\t2_output.txt --output1 t2_reverse.xml
+fc t2_reverse.xml tests\t2_reverse.xml
+.\nntask2.exe --input1 t2_reverse.xml --output1 t2_round_trip.txt
+fc t2_round_trip.txt tests\t2_output.txt
+.\nntask2.exe --input1 tests\t2_input_shared.xml --output1 t2_output_shared.txt --share
+fc t2_output_shared.txt tests\t2_output_shared.txt
+.\nntask2.exe --input1 tests\t2_input_shared.xml --output1 t2_output_shared_tree.txt --share --format tree
+fc t2_output_shared_tree.txt tests\t2_output_shared_tree.txt
+.\nntask2.exe --input1 tests\t2_input_keywords.xml --output1 t2_output_keywords.txt --share
+fc t2_output_keywords.txt tests\t2_output_keywords.txt
+.\nntask2.exe --input1 tests\t2_input_roots.xml --output1 t2_output_roots.json --format json
+fc t2_output_roots.json tests\t2_output_roots.json
+.\nntask2.exe --input1 tests\t2_input.dot --output1 t2_output_dot.txt
+fc t2_output_dot.txt tests\t2_output_dot.txt
 
 .\nntask3.exe --input1 tests\t1_output.xml --input2 tests\t3_ops.json --output1 t3_output.txt
+fc t3_output.txt tests\t3_output.txt
+.\nntask3.exe --input1 tests\t1_output.xml --input2 tests\t3_ops.json --output1 t3_output.txt --dot t3_graph.dot
+.\nntask3.exe --input1 tests\t1_output.xml --input2 tests\t3_ops.json --output1 t3_output.txt --trace
+.\nntask3.exe --input1 tests\t1_output.xml --input2 tests\t3_ops.json --output1 t3_output.txt --expr --simplify --simplified t3_simplified.xml --simplified-ops t3_simplified_ops.json
+.\nntask3.exe --input1 tests\t1_output.xml --input2 tests\t3_simplify_ops.json --output1 t3_output_simplify.txt --var x=2 --expr --simplify --simplified t3_simplified.xml --simplified-ops t3_simplified_ops.json > t3_output_simplified.txt
+fc t3_output_simplified.txt tests\t3_output_simplified.txt
+fc t3_simplified.xml tests\t3_simplified.xml
+fc t3_simplified_ops.json tests\t3_simplified_ops.json
+.\nntask3.exe --input1 tests\t1_output.xml --input2 tests\t3_ops.json --output1 t3_output_interval.txt --number interval
+fc t3_output_interval.txt tests\t3_output_interval.txt
+.\nntask3.exe --input1 tests\t1_output.xml --input2 tests\t3_ops.json --output1 t3_output.txt --merged t3_combined.json
+.\nntask3.exe --input1 tests\t3_combined.xml --split t3_graph.xml --split-ops t3_ops.json
+fc t3_graph.xml tests\t1_output.xml
 
 .\nntask4.exe convert --weights tests\t4_w.json --output tests\t4_model.json
 .\nntask4.exe run --model tests\t4_model.json --input tests\t4_x.txt --output tests\t4_output.txt